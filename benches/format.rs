@@ -0,0 +1,39 @@
+use std::hint::black_box;
+use criterion::{criterion_group, criterion_main, Criterion};
+use fastlog::appender::{FastLogRecord, LogAppender};
+use fastlog::{Config, FastLogFormatJson};
+
+/// log 10000 json records and wait them formatted,with 1/2/4/8 format workers
+fn bench_format_workers(c: &mut Criterion) {
+    struct BenchRecvLog {}
+    impl LogAppender for BenchRecvLog {
+        fn do_logs(&mut self, _records: &[FastLogRecord]) {
+            //nothing
+        }
+    }
+    for workers in [1, 2, 4, 8] {
+        fastlog::init(
+            Config::new()
+                .custom(BenchRecvLog {})
+                .format(FastLogFormatJson::new())
+                .format_workers(workers)
+                .chan_len(Some(1000000)),
+        )
+        .unwrap();
+
+        c.bench_function(&format!("bench_format_json_workers_{}", workers), |b| {
+            b.iter(|| {
+                for i in 0..10000 {
+                    log::info!(user_id = black_box(i); "Commencing yak shaving");
+                }
+                fastlog::flush().unwrap().wait();
+            });
+        });
+        fastlog::exit().unwrap();
+    }
+}
+
+criterion_group!(benches,
+    bench_format_workers,
+);
+criterion_main!(benches);
@@ -1,8 +1,21 @@
 use std::hint::black_box;
+use std::time::{Duration, Instant};
 use criterion::{criterion_group, criterion_main, Criterion};
 use fastlog::appender::{FastLogRecord, LogAppender};
 use fastlog::Config;
 
+/// the time of `iters` calls,the records are flushed after the measurement
+/// so the format thread does not share the producer time
+fn measure<F: FnMut()>(iters: u64, mut f: F) -> Duration {
+    let start = Instant::now();
+    for _ in 0..iters {
+        f();
+    }
+    let elapsed = start.elapsed();
+    fastlog::flush().unwrap().wait();
+    elapsed
+}
+
 fn bench_log(c: &mut Criterion) {
     struct BenchRecvLog {}
     impl LogAppender for BenchRecvLog {
@@ -10,16 +23,24 @@ fn bench_log(c: &mut Criterion) {
             //nothing
         }
     }
-    fastlog::init(
-        Config::new()
-            .custom(BenchRecvLog {})
-            .chan_len(Some(1000000)),
-    )
-    .unwrap();
+    //unbounded,the producers never wait the format thread
+    fastlog::init(Config::new().custom(BenchRecvLog {}).chan_len(None)).unwrap();
 
     c.bench_function("bench_log", |b| {
-        b.iter(|| {
-            black_box(log::info!("Commencing yak shaving"));
+        b.iter_custom(|iters| {
+            measure(iters, || {
+                log::info!("Commencing yak shaving");
+            })
+        });
+    });
+
+    let mut i = 0;
+    c.bench_function("bench_log_args", |b| {
+        b.iter_custom(|iters| {
+            measure(iters, || {
+                i += 1;
+                log::info!("Commencing yak shaving {}", black_box(i));
+            })
         });
     });
 }
@@ -27,4 +48,4 @@ fn bench_log(c: &mut Criterion) {
 criterion_group!(benches,
     bench_log,
 );
-criterion_main!(benches);
\ No newline at end of file
+criterion_main!(benches);
@@ -1,4 +1,3 @@
-use std::hint::black_box;
 use criterion::{criterion_group, criterion_main, Criterion};
 use fastlog::Config;
 
@@ -13,7 +12,7 @@ fn bench_log_file(c: &mut Criterion) {
 
     c.bench_function("bench_log_file", |b| {
         b.iter(|| {
-            black_box(log::info!("Commencing yak shaving"));
+            log::info!("Commencing yak shaving");
         });
 
         log::logger().flush();
@@ -1,11 +1,35 @@
+use crate::config::Config;
+use crate::error::LogError;
+use crate::pool::buffer_pool;
 use crate::WaitGroup;
-use std::time::SystemTime;
+use log::kv::{Key, Source, Value, VisitSource};
+use log::Record;
+use std::borrow::Cow;
+use std::fmt::{Display, Formatter, Write};
+use std::ops::Range;
+use std::sync::Arc;
+use std::thread::Thread;
+use std::time::{Duration, SystemTime};
 
 /// LogAppender append logs
 /// Appender will be running on single main thread,please do_log for new thread or new an Future
-pub trait LogAppender:Send {
+pub trait LogAppender: Send {
     /// Batch write log, or do nothing
     fn do_logs(&mut self, records: &[FastLogRecord]);
+
+    /// take the last write error since the last call,it is shown as `AppenderStats::last_error`
+    fn take_error(&mut self) -> Option<LogError> {
+        None
+    }
+
+    /// the max time the appender thread wait a batch before calling `tick`,it is asked before every wait.
+    /// default None,the thread waits without tick
+    fn next_tick(&self) -> Option<Duration> {
+        None
+    }
+
+    /// called when no batch is received in `next_tick`,for example to write the records kept by time
+    fn tick(&mut self) {}
 }
 
 #[derive(Clone, Debug)]
@@ -14,6 +38,9 @@ pub enum Command {
     CommandExit,
     /// Ensure that the log splitter forces splitting and saves the log
     CommandFlush(WaitGroup),
+    /// replace the config by `fastlog::reconfigure`,the records before it are written by the old appenders.
+    /// it is taken by the format thread,an appender never receive it
+    CommandReconfigure(Arc<Config>),
 }
 
 impl Command {
@@ -22,6 +49,7 @@ impl Command {
             Command::CommandRecord => 1,
             Command::CommandExit => 2,
             Command::CommandFlush(_) => 3,
+            Command::CommandReconfigure(_) => 4,
         }
     }
 }
@@ -34,17 +62,180 @@ impl PartialEq for Command {
 
 impl Eq for Command {}
 
+/// a typed value of key-values,for example `log::info!(user_id = 42; "login")`
+#[derive(Clone, Debug, PartialEq)]
+pub enum FieldValue {
+    Bool(bool),
+    I64(i64),
+    U64(u64),
+    F64(f64),
+    Str(String),
+}
+
+impl FieldValue {
+    /// capture a `log::kv::Value`,values that are not a primitive are kept by their `Display`
+    pub fn from_value(value: &Value) -> Self {
+        if let Some(v) = value.to_bool() {
+            FieldValue::Bool(v)
+        } else if let Some(v) = value.to_i64() {
+            FieldValue::I64(v)
+        } else if let Some(v) = value.to_u64() {
+            FieldValue::U64(v)
+        } else if let Some(v) = value.to_f64() {
+            FieldValue::F64(v)
+        } else if let Some(v) = value.to_borrowed_str() {
+            FieldValue::Str(v.to_string())
+        } else {
+            FieldValue::Str(value.to_string())
+        }
+    }
+
+    pub fn to_value(&self) -> Value<'_> {
+        match self {
+            FieldValue::Bool(v) => Value::from(*v),
+            FieldValue::I64(v) => Value::from(*v),
+            FieldValue::U64(v) => Value::from(*v),
+            FieldValue::F64(v) => Value::from(*v),
+            FieldValue::Str(v) => Value::from(v.as_str()),
+        }
+    }
+}
+
+impl Display for FieldValue {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FieldValue::Bool(v) => v.fmt(f),
+            FieldValue::I64(v) => v.fmt(f),
+            FieldValue::U64(v) => v.fmt(f),
+            FieldValue::F64(v) => v.fmt(f),
+            FieldValue::Str(v) => v.fmt(f),
+        }
+    }
+}
+
+/// a captured log record.
+/// static target/module_path/file/args are borrowed,a formatted args use a buffer of `BufferPool`
 #[derive(Clone, Debug)]
 pub struct FastLogRecord {
     pub command: Command,
     pub level: log::Level,
-    pub target: String,
-    pub args: String,
-    pub module_path: String,
-    pub file: String,
+    pub target: Cow<'static, str>,
+    pub args: Cow<'static, str>,
+    pub module_path: Cow<'static, str>,
+    pub file: Cow<'static, str>,
     pub line: Option<u32>,
     pub now: SystemTime,
     pub formated: String,
+    /// key-values of the record,in the order they were written
+    pub fields: Vec<(String, FieldValue)>,
+    /// the thread that logged the record,`None` when it is logged while the thread is exiting
+    pub thread: Option<Thread>,
+    /// the byte range of the level name in `formated`,it is set by the format and colored by the console
+    pub level_span: Option<Range<usize>>,
+}
+
+thread_local! {
+    static CURRENT_THREAD: Thread = std::thread::current();
+}
+
+/// the current thread,it is only a reference count increment after the first call
+pub fn current_thread() -> Option<Thread> {
+    CURRENT_THREAD.try_with(|v| v.clone()).ok()
+}
+
+impl FastLogRecord {
+    /// an empty record,for example `Command::CommandExit`
+    pub fn new_command(command: Command) -> Self {
+        FastLogRecord {
+            command,
+            level: log::Level::Info,
+            target: Cow::Borrowed(""),
+            args: Cow::Borrowed(""),
+            module_path: Cow::Borrowed(""),
+            file: Cow::Borrowed(""),
+            line: None,
+            now: SystemTime::now(),
+            formated: String::new(),
+            fields: vec![],
+            thread: None,
+            level_span: None,
+        }
+    }
+
+    /// get field value by key
+    pub fn field(&self, key: &str) -> Option<&FieldValue> {
+        self.fields.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// the thread name,or the thread id when the thread has no name
+    pub fn thread_name(&self) -> Option<Cow<'_, str>> {
+        let thread = self.thread.as_ref()?;
+        Some(match thread.name() {
+            Some(name) => Cow::Borrowed(name),
+            None => Cow::Owned(format!("{:?}", thread.id())),
+        })
+    }
+}
+
+struct FieldsVisitor<'a>(&'a mut Vec<(String, FieldValue)>);
+
+impl<'kvs> VisitSource<'kvs> for FieldsVisitor<'_> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), log::kv::Error> {
+        self.0
+            .push((key.as_str().to_string(), FieldValue::from_value(&value)));
+        Ok(())
+    }
+}
+
+impl Source for FastLogRecord {
+    fn visit<'kvs>(&'kvs self, visitor: &mut dyn VisitSource<'kvs>) -> Result<(), log::kv::Error> {
+        for (k, v) in &self.fields {
+            visitor.visit_pair(Key::from_str(k), v.to_value())?;
+        }
+        Ok(())
+    }
+}
+
+impl From<&Record<'_>> for FastLogRecord {
+    fn from(record: &Record<'_>) -> Self {
+        let mut fields = vec![];
+        let _ = record.key_values().visit(&mut FieldsVisitor(&mut fields));
+        let args = match record.args().as_str() {
+            Some(v) => Cow::Borrowed(v),
+            None => {
+                let mut buf = buffer_pool().take();
+                let _ = write!(buf, "{}", record.args());
+                Cow::Owned(buf)
+            }
+        };
+        //the default target is module_path!()
+        let target = match record.module_path_static() {
+            Some(v) if v == record.target() => Cow::Borrowed(v),
+            _ => Cow::Owned(record.target().to_string()),
+        };
+        let module_path = match record.module_path_static() {
+            Some(v) => Cow::Borrowed(v),
+            None => Cow::Owned(record.module_path().unwrap_or_default().to_string()),
+        };
+        let file = match record.file_static() {
+            Some(v) => Cow::Borrowed(v),
+            None => Cow::Owned(record.file().unwrap_or_default().to_string()),
+        };
+        FastLogRecord {
+            command: Command::CommandRecord,
+            level: record.level(),
+            target,
+            args,
+            module_path,
+            file,
+            line: record.line(),
+            now: SystemTime::now(),
+            formated: String::new(),
+            fields,
+            thread: current_thread(),
+            level_span: None,
+        }
+    }
 }
 
 /// format record data
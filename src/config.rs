@@ -1,5 +1,6 @@
-use crate::appender::{LogAppender, RecordFormat};
+use crate::appender::{Command, FastLogRecord, LogAppender, RecordFormat};
 use crate::consts::LogSize;
+use crate::error::LogError;
 use crate::filter::Filter;
 use crate::plugin::console::ConsoleAppender;
 use crate::plugin::file::FileAppender;
@@ -7,11 +8,15 @@ use crate::plugin::file_loop::FileLoopAppender;
 use crate::plugin::file_split::{
     CanRollingPack, FileSplitAppender, Keep, Packer, RawFile, SplitFile,
 };
+use crate::stats::Diagnostic;
 use crate::FastLogFormat;
 use dark_std::sync::SyncVec;
 use log::LevelFilter;
 use parking_lot::Mutex;
 use std::fmt::{Debug, Formatter};
+use std::io::Write;
+use std::sync::Arc;
+use std::time::Duration;
 
 /// the fastlog Config
 /// for example:
@@ -24,7 +29,7 @@ use std::fmt::{Debug, Formatter};
 pub struct Config {
     /// Each appender is responsible for printing its own business
     /// every LogAppender have one thread(need Mutex) access this.
-    pub appends: SyncVec<Mutex<Box<dyn LogAppender>>>,
+    pub appends: SyncVec<AppenderConfig>,
     /// the log level filter
     pub level: LevelFilter,
     /// filter log
@@ -33,6 +38,57 @@ pub struct Config {
     pub format: Box<dyn RecordFormat>,
     /// the channel length,default None(Unbounded channel)
     pub chan_len: Option<usize>,
+    /// what to do when the bounded channel is full,default Block
+    pub overflow: OverflowPolicy,
+    /// log panics at ERROR level and flush,default false
+    pub capture_panics: bool,
+    /// receive the appender faults,default None(print to stderr)
+    pub diagnostics: Option<DiagnosticsHook>,
+    /// the threads format records,default 1
+    pub format_workers: usize,
+    /// buffer records in producer shards,default None(send every record to the channel)
+    pub sharded: Option<ShardConfig>,
+    /// the max time `fastlog::reconfigure` with this config wait the old appenders write their records,default 10 seconds
+    pub drain_timeout: Duration,
+}
+
+/// buffer the records of producer threads in shards,and hand off whole batches to the format thread.
+/// a thread always use the same shard,so the records of a thread keep their order
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShardConfig {
+    /// the number of shards,default the available parallelism
+    pub shards: usize,
+    /// a shard hand off its records when it has `batch_len` records
+    pub batch_len: usize,
+    /// the max time a record wait in a quiet shard before the format thread take it
+    pub max_staleness: Duration,
+}
+
+/// see `Config::diagnostics`
+pub type DiagnosticsHook = Box<dyn Fn(&Diagnostic) + Send + Sync>;
+
+/// see `AppenderConfig::restart`
+pub type AppenderFactory = Box<dyn Fn() -> Result<Box<dyn LogAppender>, LogError> + Send + Sync>;
+
+/// what `log` do when the bounded channel(`Config::chan_len`) is full.
+/// dropped records are counted and written as an "N records dropped" record.
+/// it is also used by the appender channels,records dropped there are counted in `fastlog::appender_stats`.
+/// flush and exit commands are never dropped,they block on the main channel,
+/// and wait aside without blocking when an appender channel is full(but `Block`)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// block the caller until the channel has space
+    #[default]
+    Block,
+    /// block the caller at most the duration,then drop the record
+    BlockTimeout(Duration),
+    /// drop the record being sent
+    DropNewest,
+    /// drop the oldest record in the channel to make space
+    DropOldest,
+    /// drop TRACE/DEBUG when the channel is half full,drop INFO/WARN when the channel is full,
+    /// ERROR is not dropped(block),but a stalled appender channel hold the ERROR records at most 1s then drop them
+    ShedByLevel,
 }
 
 impl Debug for Config {
@@ -41,6 +97,12 @@ impl Debug for Config {
             .field("appends", &self.appends.len())
             .field("level", &self.level)
             .field("chan_len", &self.chan_len)
+            .field("overflow", &self.overflow)
+            .field("capture_panics", &self.capture_panics)
+            .field("diagnostics", &self.diagnostics.is_some())
+            .field("format_workers", &self.format_workers)
+            .field("sharded", &self.sharded)
+            .field("drain_timeout", &self.drain_timeout)
             .finish()
     }
 }
@@ -53,6 +115,12 @@ impl Default for Config {
             filters: SyncVec::new(),
             format: Box::new(FastLogFormat::new()),
             chan_len: None,
+            overflow: OverflowPolicy::default(),
+            capture_panics: false,
+            diagnostics: None,
+            format_workers: 1,
+            sharded: None,
+            drain_timeout: Duration::from_secs(10),
         }
     }
 }
@@ -62,6 +130,15 @@ impl Config {
         Self::default()
     }
 
+    /// the max level of all appenders,an appender level can not be greater than `Config::level`
+    pub fn max_level(&self) -> LevelFilter {
+        let mut max = LevelFilter::Off;
+        for x in self.appends.iter() {
+            max = max.max(x.level.unwrap_or(self.level));
+        }
+        max.min(self.level)
+    }
+
     /// set log LevelFilter
     pub fn level(mut self, level: LevelFilter) -> Self {
         self.level = level;
@@ -87,21 +164,17 @@ impl Config {
     }
     /// add a ConsoleAppender
     pub fn console(self) -> Self {
-        self.appends.push(Mutex::new(Box::new(ConsoleAppender {})));
-        self
+        self.append(AppenderConfig::new(ConsoleAppender::new()))
     }
     /// add a FileAppender
     pub fn file(self, file: &str) -> Self {
-        self.appends
-            .push(Mutex::new(Box::new(FileAppender::new(file).unwrap())));
-        self
+        self.append(AppenderConfig::new(FileAppender::new(file).unwrap()))
     }
     /// add a FileLoopAppender
     pub fn file_loop(self, file: &str, max_temp_size: LogSize) -> Self {
-        self.appends.push(Mutex::new(Box::new(
+        self.append(AppenderConfig::new(
             FileLoopAppender::new(file, max_temp_size).expect("make file_loop fail"),
-        )));
-        self
+        ))
     }
     /// add a FileSplitAppender
     pub fn file_split<
@@ -115,7 +188,7 @@ impl Config {
         keeper: K,
         packer: P,
     ) -> Self {
-        self.appends.push(Mutex::new(Box::new(
+        self.append(AppenderConfig::new(
             FileSplitAppender::new::<RawFile>(
                 file_path,
                 Box::new(rolling),
@@ -123,8 +196,7 @@ impl Config {
                 Box::new(packer),
             )
             .expect("new split file fail"),
-        )));
-        self
+        ))
     }
 
     /// add a SplitAppender
@@ -160,7 +232,7 @@ impl Config {
         packer: P,
         how_pack: H,
     ) -> Self {
-        self.appends.push(Mutex::new(Box::new(
+        self.append(AppenderConfig::new(
             FileSplitAppender::new::<F>(
                 file_path,
                 Box::new(how_pack),
@@ -168,8 +240,7 @@ impl Config {
                 Box::new(packer),
             )
             .expect("new split file fail"),
-        )));
-        self
+        ))
     }
     /// add a custom LogAppender
     pub fn custom<Appender: LogAppender + 'static>(self, arg: Appender) -> Self {
@@ -178,7 +249,30 @@ impl Config {
 
     /// add a LogAppender
     pub fn add_appender<Appender: LogAppender + 'static>(self, arg: Appender) -> Self {
-        self.appends.push(Mutex::new(Box::new(arg)));
+        self.append(AppenderConfig::new(arg))
+    }
+
+    /// add a LogAppender with its own level,filters and format
+    /// for example:
+    /// ```rust
+    /// use fastlog::{AppenderConfig, Config, FastLogFormatJson};
+    /// use fastlog::plugin::console::ConsoleAppender;
+    /// use fastlog::plugin::file::FileAppender;
+    /// use log::LevelFilter;
+    /// fn new(){
+    ///  fastlog::init(
+    ///         Config::new()
+    ///             .append(AppenderConfig::new(ConsoleAppender::new()).level(LevelFilter::Info))
+    ///             .append(
+    ///                 AppenderConfig::new(FileAppender::new("target/logs/app.json").unwrap())
+    ///                     .level(LevelFilter::Debug)
+    ///                     .format(FastLogFormatJson::new()),
+    ///             ),
+    ///     );
+    /// }
+    /// ```
+    pub fn append(self, append: AppenderConfig) -> Self {
+        self.appends.push(append);
         self
     }
 
@@ -187,4 +281,282 @@ impl Config {
         self.chan_len = len;
         self
     }
+
+    /// set what to do when the bounded channel is full
+    /// for example:
+    /// ```rust
+    /// use fastlog::{Config, OverflowPolicy};
+    /// fn new(){
+    ///    fastlog::init(Config::new().console().chan_len(Some(10000)).overflow(OverflowPolicy::ShedByLevel)).unwrap();
+    /// }
+    /// ```
+    pub fn overflow(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow = policy;
+        self
+    }
+
+    /// install a panic hook,it log the panic payload,location,thread name and backtrace at ERROR level,
+    /// then wait the appenders flush(at most a few seconds) before the previous hook run
+    /// for example:
+    /// ```rust
+    /// use fastlog::Config;
+    /// fn new(){
+    ///    fastlog::init(Config::new().file("target/test.log").capture_panics()).unwrap();
+    /// }
+    /// ```
+    pub fn capture_panics(mut self) -> Self {
+        self.capture_panics = true;
+        self
+    }
+
+    /// set the hook of appender faults(panic,write error,restart)
+    /// for example:
+    /// ```rust
+    /// use fastlog::Config;
+    /// fn new(){
+    ///    fastlog::init(Config::new().console().diagnostics(|x| eprintln!("[log] {}", x))).unwrap();
+    /// }
+    /// ```
+    pub fn diagnostics<F: Fn(&Diagnostic) + Send + Sync + 'static>(mut self, hook: F) -> Self {
+        self.diagnostics = Some(Box::new(hook));
+        self
+    }
+
+    /// set the threads format records,a big batch is split into chunks formatted in parallel,
+    /// the order of records is kept. it helps a slow format(for example json) on many cores
+    /// for example:
+    /// ```rust
+    /// use fastlog::{Config, FastLogFormatJson};
+    /// fn new(){
+    ///    fastlog::init(Config::new().console().format(FastLogFormatJson::new()).format_workers(4)).unwrap();
+    /// }
+    /// ```
+    pub fn format_workers(mut self, workers: usize) -> Self {
+        self.format_workers = workers.max(1);
+        self
+    }
+
+    /// buffer records in per-thread shards and hand off batches of `batch_len` records,
+    /// it reduce the contention of many producer threads on the channel.
+    /// a record wait at most `max_staleness` in a quiet shard,`flush` and `exit` hand off all shards.
+    /// the batches block when the channel is full(`OverflowPolicy` is not used),
+    /// and `fastlog::reconfigure` keep the shards like `chan_len`
+    /// for example:
+    /// ```rust
+    /// use fastlog::Config;
+    /// use std::time::Duration;
+    /// fn new(){
+    ///    fastlog::init(Config::new().console().sharded(64, Duration::from_millis(10))).unwrap();
+    /// }
+    /// ```
+    pub fn sharded(mut self, batch_len: usize, max_staleness: Duration) -> Self {
+        self.sharded = Some(ShardConfig {
+            shards: std::thread::available_parallelism()
+                .map(|v| v.get())
+                .unwrap_or(1),
+            batch_len: batch_len.max(1),
+            max_staleness,
+        });
+        self
+    }
+
+    /// set the max time `fastlog::reconfigure` with this config wait the old appenders write their records,
+    /// an old appender not drained(for example stalled) is left running and reported as `Diagnostic::Undrained`
+    pub fn drain_timeout(mut self, timeout: Duration) -> Self {
+        self.drain_timeout = timeout;
+        self
+    }
+
+    /// report an appender fault to the diagnostics hook,default is stderr(a closed stderr is ignored)
+    pub fn report(&self, diagnostic: Diagnostic) {
+        match &self.diagnostics {
+            Some(hook) => hook(&diagnostic),
+            None => {
+                let _ = writeln!(std::io::stderr().lock(), "[fastlog] {}", diagnostic);
+            }
+        }
+    }
+}
+
+/// an appender with its own level,filters and format.
+/// the records must pass `Config::level` and `Config::filters` first,
+/// `format` None means use `Config::format`
+pub struct AppenderConfig {
+    /// the appender name,default is the type name of appender
+    pub name: String,
+    pub appender: Mutex<Box<dyn LogAppender>>,
+    /// the appender level,None means use `Config::level`
+    pub level: Option<LevelFilter>,
+    pub filters: Vec<Box<dyn Filter>>,
+    /// the appenders with the same `Arc` share the formatted records
+    pub format: Option<Arc<dyn RecordFormat>>,
+    /// the appender channel length(batches),None means use `Config::chan_len`
+    pub chan_len: Option<usize>,
+    /// what to do when the appender channel is full,None means use `Config::overflow`
+    pub overflow: Option<OverflowPolicy>,
+    /// make a new appender after the appender panicked
+    pub restart: Option<AppenderFactory>,
+    /// write the records when the appender panicked(and not restarted) or return an error
+    pub fallback: Option<Mutex<Box<dyn LogAppender>>>,
+}
+
+impl AppenderConfig {
+    pub fn new<Appender: LogAppender + 'static>(appender: Appender) -> Self {
+        Self {
+            name: std::any::type_name::<Appender>().to_string(),
+            appender: Mutex::new(Box::new(appender)),
+            level: None,
+            filters: vec![],
+            format: None,
+            chan_len: None,
+            overflow: None,
+            restart: None,
+            fallback: None,
+        }
+    }
+
+    /// set appender name
+    pub fn name(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// set appender LevelFilter
+    pub fn level(mut self, level: LevelFilter) -> Self {
+        self.level = Some(level);
+        self
+    }
+
+    /// add appender Filter
+    pub fn add_filter<F: Filter + 'static>(mut self, filter: F) -> Self {
+        self.filters.push(Box::new(filter));
+        self
+    }
+
+    /// set appender format.
+    /// the records of an appender with own format are cloned and formatted for it,use `shared_format` for the appenders with the same format
+    pub fn format<F: RecordFormat + 'static>(mut self, format: F) -> Self {
+        self.format = Some(Arc::new(format));
+        self
+    }
+
+    /// set appender format shared with other appenders,the records are formatted once for all of them
+    /// for example:
+    /// ```rust
+    /// use fastlog::appender::RecordFormat;
+    /// use fastlog::{AppenderConfig, Config, FastLogFormatJson};
+    /// use fastlog::plugin::file::FileAppender;
+    /// use std::sync::Arc;
+    /// fn new(){
+    ///  let json: Arc<dyn RecordFormat> = Arc::new(FastLogFormatJson::new());
+    ///  fastlog::init(
+    ///         Config::new()
+    ///             .console()
+    ///             .append(AppenderConfig::new(FileAppender::new("target/logs/a.json").unwrap()).shared_format(json.clone()))
+    ///             .append(AppenderConfig::new(FileAppender::new("target/logs/b.json").unwrap()).shared_format(json)),
+    ///     );
+    /// }
+    /// ```
+    pub fn shared_format(mut self, format: Arc<dyn RecordFormat>) -> Self {
+        self.format = Some(format);
+        self
+    }
+
+    /// set the appender channel length,a slow appender with a bounded channel
+    /// and a dropping `OverflowPolicy` only lose its own records
+    /// for example:
+    /// ```rust
+    /// use fastlog::{AppenderConfig, Config, OverflowPolicy};
+    /// use fastlog::plugin::file::FileAppender;
+    /// fn new(){
+    ///  fastlog::init(
+    ///         Config::new().console().append(
+    ///             AppenderConfig::new(FileAppender::new("target/logs/nfs.log").unwrap())
+    ///                 .chan_len(100)
+    ///                 .overflow(OverflowPolicy::DropOldest),
+    ///         ),
+    ///     );
+    /// }
+    /// ```
+    pub fn chan_len(mut self, len: usize) -> Self {
+        self.chan_len = Some(len);
+        self
+    }
+
+    /// set what to do when the appender channel is full
+    pub fn overflow(mut self, policy: OverflowPolicy) -> Self {
+        self.overflow = Some(policy);
+        self
+    }
+
+    /// make a new appender when the appender panicked,retry at most once a second until success
+    /// for example:
+    /// ```rust
+    /// use fastlog::{AppenderConfig, Config};
+    /// use fastlog::plugin::console::ConsoleAppender;
+    /// use fastlog::plugin::file::FileAppender;
+    /// fn new(){
+    ///  fastlog::init(
+    ///         Config::new().append(
+    ///             AppenderConfig::new(FileAppender::new("target/logs/app.log").unwrap())
+    ///                 .restart(|| FileAppender::new("target/logs/app.log"))
+    ///                 .fallback(ConsoleAppender::new()),
+    ///         ),
+    ///     );
+    /// }
+    /// ```
+    pub fn restart<A, F>(mut self, factory: F) -> Self
+    where
+        A: LogAppender + 'static,
+        F: Fn() -> Result<A, LogError> + Send + Sync + 'static,
+    {
+        self.restart = Some(Box::new(move || {
+            factory().map(|v| Box::new(v) as Box<dyn LogAppender>)
+        }));
+        self
+    }
+
+    /// set the appender that write the records while the appender is unhealthy,for example `ConsoleAppender`
+    pub fn fallback<A: LogAppender + 'static>(mut self, appender: A) -> Self {
+        self.fallback = Some(Mutex::new(Box::new(appender)));
+        self
+    }
+
+    /// is the appender need filter records
+    pub fn has_filter(&self) -> bool {
+        self.level.is_some() || !self.filters.is_empty()
+    }
+
+    /// if return true=do_log/false=not_log,the command records always return true
+    pub fn do_log(&self, record: &FastLogRecord) -> bool {
+        if record.command != Command::CommandRecord {
+            return true;
+        }
+        if let Some(level) = self.level {
+            if record.level > level {
+                return false;
+            }
+        }
+        for filter in &self.filters {
+            if !filter.do_log_record(record) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+impl Debug for AppenderConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AppenderConfig")
+            .field("name", &self.name)
+            .field("level", &self.level)
+            .field("filters", &self.filters.len())
+            .field("format", &self.format.is_some())
+            .field("chan_len", &self.chan_len)
+            .field("overflow", &self.overflow)
+            .field("restart", &self.restart.is_some())
+            .field("fallback", &self.fallback.is_some())
+            .finish()
+    }
 }
@@ -10,6 +10,7 @@ pub enum LogSize {
     EB(usize),
 }
 
+#[allow(clippy::len_without_is_empty)]
 impl LogSize {
     pub fn len(&self) -> usize {
         match self {
@@ -1,30 +1,103 @@
-use crate::appender::{Command, FastLogRecord};
-use crate::config::Config;
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use crate::config::{AppenderConfig, Config, OverflowPolicy, ShardConfig};
 use crate::error::LogError;
-use crate::{chan, spawn, Receiver, SendError, Sender, WaitGroup};
+use crate::pool::buffer_pool;
+use crate::stats::{AppenderStats, Diagnostic, LogStats};
+use crate::{chan, spawn, JoinHandle, Receiver, SendError, Sender, WaitGroup};
+use arc_swap::ArcSwapOption;
+use crossbeam_utils::CachePadded;
 use log::{LevelFilter, Log, Metadata, Record};
+use parking_lot::{Mutex, RwLock};
+use std::cell::Cell;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, OnceLock};
-use std::time::SystemTime;
+use std::time::{Duration, Instant};
 
 pub static LOGGER: OnceLock<Logger> = OnceLock::new();
 
+/// `log::set_logger` can only be called once,so `init` after `shutdown` skip it
+static LOGGER_SET: AtomicBool = AtomicBool::new(false);
+
+/// the min interval of the "N records dropped" record
+const DROPPED_REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// the panic hook is installed once,it checks `Config::capture_panics` of the running config
+static PANIC_HOOK_SET: AtomicBool = AtomicBool::new(false);
+
+/// the max time the panic hook wait to send the record and flush
+const PANIC_FLUSH_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// the min records of a chunk formatted by a format worker
+const FORMAT_CHUNK_MIN: usize = 64;
+
+/// the min interval of `AppenderConfig::restart`
+const RESTART_INTERVAL: Duration = Duration::from_secs(1);
+
+/// the max time the format thread wait a full appender channel for ERROR records(`OverflowPolicy::ShedByLevel`),
+/// a stalled appender is not waited again until a batch is sent to it
+const SHED_ERROR_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// the shard of the next producer thread
+static NEXT_SHARD: AtomicUsize = AtomicUsize::new(0);
+
+/// the threads left by the format thread,they are joined by `fastlog::shutdown`
+type FormatThreadExit = (Vec<AppenderWorker>, FormatPool);
+
+thread_local! {
+    /// the format thread and appender threads,the panic hook must not wait them flush
+    static LOGGER_THREAD: Cell<bool> = const { Cell::new(false) };
+    /// the shard of this producer thread
+    static SHARD: usize = NEXT_SHARD.fetch_add(1, Ordering::Relaxed);
+}
+
 /// get Logger,but you must call `fastlog::init`
 pub fn logger() -> &'static Logger {
-    LOGGER.get_or_init(|| Logger::default())
+    LOGGER.get_or_init(Logger::default)
 }
 
 pub struct Logger {
-    pub cfg: OnceLock<Config>,
-    pub send: OnceLock<Sender<FastLogRecord>>,
-    pub recv: OnceLock<Receiver<FastLogRecord>>,
+    /// the running config,it can be replaced by `fastlog::reconfigure`
+    pub cfg: RwLock<Option<Arc<Config>>>,
+    pub send: RwLock<Option<Sender<FastLogRecord>>>,
+    pub recv: RwLock<Option<Receiver<FastLogRecord>>>,
+    /// the format thread,it return the appender threads and format workers when exit
+    handle: Mutex<Option<JoinHandle<FormatThreadExit>>>,
+    /// the snapshot of `send`,`cfg` and `shards` loaded by `log` without lock
+    producer: ArcSwapOption<Producer>,
+    /// records dropped by `OverflowPolicy` and not reported yet
+    dropped: AtomicU64,
+    /// counters of `fastlog::stats`,accepted and filtered are counted by the shard of the producer thread
+    counters: Box<[CachePadded<ProducerCounters>]>,
+    dropped_total: AtomicU64,
+    /// the producer shards of `Config::sharded`
+    shards: RwLock<Option<Arc<ProducerShards>>>,
+    /// the appender channels of the running config,for `fastlog::appender_stats`
+    channels: RwLock<Vec<Arc<AppenderChannel>>>,
+    /// changed by `shutdown`,a format thread not exit before the timeout
+    /// must not pick up the config of the next `init`
+    epoch: AtomicU64,
 }
 
 impl Logger {
+    #[allow(clippy::should_implement_trait)]
     pub fn default() -> Self {
         Self {
-            cfg: OnceLock::default(),
-            send: OnceLock::default(),
-            recv: OnceLock::default(),
+            cfg: RwLock::new(None),
+            send: RwLock::new(None),
+            recv: RwLock::new(None),
+            handle: Mutex::new(None),
+            producer: ArcSwapOption::empty(),
+            dropped: AtomicU64::new(0),
+            counters: (0..std::thread::available_parallelism()
+                .map(|v| v.get())
+                .unwrap_or(1))
+                .map(|_| CachePadded::new(ProducerCounters::default()))
+                .collect(),
+            dropped_total: AtomicU64::new(0),
+            shards: RwLock::new(None),
+            channels: RwLock::new(vec![]),
+            epoch: AtomicU64::new(0),
         }
     }
 
@@ -37,19 +110,11 @@ impl Logger {
     }
 
     /// print no other info
+    #[allow(clippy::result_large_err)]
     pub fn print(&self, log: String) -> Result<(), SendError<FastLogRecord>> {
-        let fastlog_record = FastLogRecord {
-            command: Command::CommandRecord,
-            level: log::Level::Info,
-            target: "".to_string(),
-            args: "".to_string(),
-            module_path: "".to_string(),
-            file: "".to_string(),
-            line: None,
-            now: SystemTime::now(),
-            formated: log,
-        };
-        if let Some(send) = logger().send.get() {
+        let mut fastlog_record = FastLogRecord::new_command(Command::CommandRecord);
+        fastlog_record.formated = log;
+        if let Some(send) = logger().send.read().as_ref() {
             send.send(fastlog_record)
         } else {
             // Ok(())
@@ -60,6 +125,73 @@ impl Logger {
     pub fn wait(&self) {
         self.flush();
     }
+
+    /// publish `send`,`cfg` and `shards` to `log`,or None after `shutdown`
+    fn publish(&self, producer: Option<Producer>) {
+        self.producer.store(producer.map(Arc::new));
+    }
+
+    /// the counters of this producer thread
+    fn counters(&self) -> &ProducerCounters {
+        &self.counters[SHARD.with(|v| *v) % self.counters.len()]
+    }
+
+    /// send record by the OverflowPolicy,return false if the record is dropped
+    fn send_record(&self, producer: &Producer, mut record: FastLogRecord) -> bool {
+        let send = &producer.send;
+        let sent = match producer.cfg.overflow {
+            OverflowPolicy::Block => return send.send(record).is_ok(),
+            OverflowPolicy::BlockTimeout(timeout) => match send.send_timeout(record, timeout) {
+                Ok(_) => true,
+                Err(crossbeam_channel::SendTimeoutError::Timeout(_)) => false,
+                Err(crossbeam_channel::SendTimeoutError::Disconnected(_)) => return false,
+            },
+            OverflowPolicy::DropNewest => match send.try_send(record) {
+                Ok(_) => true,
+                Err(crossbeam_channel::TrySendError::Full(_)) => false,
+                Err(crossbeam_channel::TrySendError::Disconnected(_)) => return false,
+            },
+            OverflowPolicy::DropOldest => loop {
+                match send.try_send(record) {
+                    Ok(_) => return true,
+                    Err(crossbeam_channel::TrySendError::Full(v)) => {
+                        record = v;
+                        let mut evicted = producer.evicted.lock();
+                        match producer.recv.try_recv() {
+                            Ok(oldest) if oldest.command == Command::CommandRecord => {
+                                self.dropped.fetch_add(1, Ordering::Relaxed);
+                                self.dropped_total.fetch_add(1, Ordering::Relaxed);
+                            }
+                            //never drop commands,the format thread take it before the channel
+                            Ok(oldest) => evicted.push(oldest),
+                            Err(_) => {}
+                        }
+                    }
+                    Err(crossbeam_channel::TrySendError::Disconnected(_)) => return false,
+                }
+            },
+            OverflowPolicy::ShedByLevel => {
+                let half_full = match send.capacity() {
+                    Some(cap) => send.len() * 2 >= cap,
+                    None => false,
+                };
+                match record.level {
+                    log::Level::Error => return send.send(record).is_ok(),
+                    log::Level::Debug | log::Level::Trace if half_full => false,
+                    _ => match send.try_send(record) {
+                        Ok(_) => true,
+                        Err(crossbeam_channel::TrySendError::Full(_)) => false,
+                        Err(crossbeam_channel::TrySendError::Disconnected(_)) => return false,
+                    },
+                }
+            }
+        };
+        if !sent {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            self.dropped_total.fetch_add(1, Ordering::Relaxed);
+        }
+        sent
+    }
 }
 
 impl Log for Logger {
@@ -67,80 +199,778 @@ impl Log for Logger {
         metadata.level() <= self.get_level()
     }
     fn log(&self, record: &Record) {
-        if let Some(filter) = logger().cfg.get() {
-            if let Some(send) = logger().send.get() {
-                for filter in filter.filters.iter() {
-                    if !filter.do_log(record) {
-                        return;
-                    }
+        let producer = logger().producer.load();
+        if let Some(producer) = producer.as_ref() {
+            for filter in producer.cfg.filters.iter() {
+                if !filter.do_log(record) {
+                    logger().counters().filtered.fetch_add(1, Ordering::Relaxed);
+                    return;
                 }
-                let _ = send.send(FastLogRecord {
-                    command: Command::CommandRecord,
-                    level: record.level(),
-                    target: record.metadata().target().to_string(),
-                    args: record.args().to_string(),
-                    module_path: record.module_path().unwrap_or_default().to_string(),
-                    file: record.file().unwrap_or_default().to_string(),
-                    line: record.line().clone(),
-                    now: SystemTime::now(),
-                    formated: String::new(),
-                });
+            }
+            let record = FastLogRecord::from(record);
+            let sent = match &producer.shards {
+                Some(shards) => shards.push(record),
+                None => logger().send_record(producer, record),
+            };
+            if sent {
+                logger().counters().accepted.fetch_add(1, Ordering::Relaxed);
             }
         }
     }
     fn flush(&self) {
-        match flush() {
-            Ok(v) => {
-                v.wait();
-            }
-            Err(_) => {}
+        if let Ok(v) = flush() {
+            v.wait();
         }
     }
 }
 
+/// init the logger,it can be called again after `fastlog::shutdown`
 pub fn init(config: Config) -> Result<&'static Logger, LogError> {
     if config.appends.is_empty() {
         return Err(LogError::from("[fastlog] appends can not be empty!"));
     }
-    let (s, r) = chan(config.chan_len);
-    logger()
-        .send
-        .set(s)
-        .map_err(|_| LogError::from("set fail"))?;
-    logger()
-        .recv
-        .set(r)
-        .map_err(|_| LogError::from("set fail"))?;
-    logger().set_level(config.level);
-    logger()
-        .cfg
-        .set(config)
-        .map_err(|_| LogError::from("set fail="))?;
+    let mut send = logger().send.write();
+    if send.is_some() {
+        return Err(LogError::from("[fastlog] logger is already init"));
+    }
     //main recv data
-    log::set_logger(logger())
-        .map(|()| log::set_max_level(logger().cfg.get().expect("logger cfg is none").level))
-        .map_err(|e| LogError::from(e))?;
-
-    let mut receiver_vec = vec![];
-    let mut sender_vec: Vec<Sender<Arc<Vec<FastLogRecord>>>> = vec![];
-    let cfg = logger().cfg.get().expect("logger cfg is none");
-    for a in cfg.appends.iter() {
-        let (s, r) = chan(cfg.chan_len);
-        sender_vec.push(s);
-        receiver_vec.push((r, a));
-    }
-    for (receiver, appender) in receiver_vec {
-        spawn(move || {
+    if !LOGGER_SET.load(Ordering::SeqCst) {
+        log::set_logger(logger()).map_err(LogError::from)?;
+        LOGGER_SET.store(true, Ordering::SeqCst);
+    }
+    if config.capture_panics {
+        set_panic_hook();
+    }
+    let (s, r) = chan(config.chan_len);
+    let shards = config
+        .sharded
+        .map(|v| Arc::new(ProducerShards::new(v, config.chan_len)));
+    *logger().shards.write() = shards.clone();
+    logger().set_level(config.max_level());
+    let cfg = Arc::new(config);
+    let epoch = {
+        let mut cfg_lock = logger().cfg.write();
+        *cfg_lock = Some(cfg.clone());
+        logger().epoch.load(Ordering::SeqCst)
+    };
+    *logger().recv.write() = Some(r.clone());
+    let evicted = Arc::new(Mutex::new(vec![]));
+    logger().publish(Some(Producer {
+        send: s.clone(),
+        recv: r.clone(),
+        cfg: cfg.clone(),
+        shards: shards.clone(),
+        evicted: evicted.clone(),
+    }));
+
+    let handle = spawn(move || {
+        LOGGER_THREAD.with(|v| v.set(true));
+        let recv = r;
+        let mut cfg = cfg;
+        let mut appenders = spawn_appenders(&cfg);
+        publish_channels(epoch, &appenders);
+        let mut pool = FormatPool::new(cfg.format_workers);
+        let mut last_report = Instant::now();
+        let mut last_drain = Instant::now();
+        loop {
+            let mut remain = Vec::with_capacity(recv.len());
+            if let Some(shards) = &shards {
+                //the commands are taken after the shard batches sent before them
+                shards.recv(&recv, &mut remain, &mut last_drain);
+            } else {
+                if recv.is_empty() {
+                    let mut select = crossbeam_channel::Select::new();
+                    select.recv(&recv);
+                    select.ready();
+                }
+                //the commands taken by `OverflowPolicy::DropOldest` were the head of the channel
+                let mut evicted = evicted.lock();
+                remain.append(&mut evicted);
+                //merge log,at most the records in channel now,fast producers can not grow the batch forever
+                for _ in 0..recv.len().max(1) {
+                    match recv.try_recv() {
+                        Ok(v) => {
+                            remain.push(v);
+                        }
+                        Err(crossbeam_channel::TryRecvError::Disconnected) if remain.is_empty() => {
+                            //all sender dropped,make appenders exit
+                            remain.push(FastLogRecord::new_command(Command::CommandExit));
+                            break;
+                        }
+                        Err(_) => {
+                            break;
+                        }
+                    }
+                }
+                drop(evicted);
+            }
+            //report dropped records,at once if there is a command
+            let command = remain
+                .iter()
+                .position(|x| x.command != Command::CommandRecord);
+            if command.is_some() || last_report.elapsed() >= DROPPED_REPORT_INTERVAL {
+                last_report = Instant::now();
+                let dropped = logger().dropped.swap(0, Ordering::Relaxed);
+                if dropped > 0 {
+                    let mut record = FastLogRecord::new_command(Command::CommandRecord);
+                    record.level = log::Level::Warn;
+                    record.target = "fastlog".into();
+                    record.module_path = "fastlog".into();
+                    record.args = format!("{} records dropped", dropped).into();
+                    remain.insert(command.unwrap_or(remain.len()), record);
+                }
+            }
+            //config is replaced at the marker,the records before it are written by the old appenders
+            while let Some(at) = remain
+                .iter()
+                .position(|x| matches!(x.command, Command::CommandReconfigure(_)))
+            {
+                let rest = remain.split_off(at + 1);
+                let marker = remain.pop();
+                write_records(&cfg, &appenders, &pool, remain);
+                remain = rest;
+                if let Some(Command::CommandReconfigure(new_cfg)) = marker.map(|x| x.command) {
+                    let deadline = Some(Instant::now() + new_cfg.drain_timeout);
+                    for name in exit_appenders(appenders, deadline) {
+                        cfg.report(Diagnostic::Undrained { appender: name });
+                    }
+                    for name in pool.join(deadline) {
+                        cfg.report(Diagnostic::Undrained { appender: name });
+                    }
+                    cfg = new_cfg;
+                    appenders = spawn_appenders(&cfg);
+                    publish_channels(epoch, &appenders);
+                    pool = FormatPool::new(cfg.format_workers);
+                }
+            }
+            let exit = remain.iter().any(|x| x.command.eq(&Command::CommandExit));
+            write_records(&cfg, &appenders, &pool, remain);
+            if exit {
+                break;
+            }
+        }
+        (appenders, pool)
+    });
+    *logger().handle.lock() = Some(handle);
+    *send = Some(s);
+    Ok(logger())
+}
+
+/// format the records and send them to the appenders
+fn write_records(
+    cfg: &Arc<Config>,
+    appenders: &[AppenderWorker],
+    pool: &FormatPool,
+    mut remain: Vec<FastLogRecord>,
+) {
+    //appenders with own format,format only the records they need.
+    //the appenders sharing a format are formatted once,`exact` is false if the records are of the whole group
+    let appends: Vec<&AppenderConfig> = cfg.appends.iter().collect();
+    let mut owned: Vec<Option<(Arc<Vec<FastLogRecord>>, bool)>> = Vec::with_capacity(appends.len());
+    for (index, append) in appends.iter().enumerate() {
+        let format = match &append.format {
+            Some(v) => v,
+            None => {
+                owned.push(None);
+                continue;
+            }
+        };
+        let same = |x: &&AppenderConfig| x.format.as_ref().is_some_and(|v| Arc::ptr_eq(v, format));
+        if let Some(first) = appends[..index].iter().position(same) {
+            let group = owned[first].as_ref().map(|(v, _)| (v.clone(), false));
+            owned.push(group);
+            continue;
+        }
+        let group: Vec<&AppenderConfig> = appends[index..].iter().copied().filter(same).collect();
+        let mut records: Vec<FastLogRecord> = remain
+            .iter()
+            .filter(|x| group.iter().any(|append| append.do_log(x)))
+            .cloned()
+            .collect();
+        pool.format(cfg, Some(index), &mut records);
+        owned.push(Some((Arc::new(records), group.len() == 1)));
+    }
+    //other appenders share the records formatted by Config::format
+    let mut shared = None;
+    if owned.iter().any(|x| x.is_none()) {
+        pool.format(cfg, None, &mut remain);
+        shared = Some(Arc::new(remain));
+    }
+    for ((worker, records), append) in appenders.iter().zip(owned).zip(appends) {
+        let data = match (records, &shared) {
+            (Some((records, exact)), _) if exact || !append.has_filter() => records,
+            (Some((records, _)), _) => {
+                let data = records
+                    .iter()
+                    .filter(|x| append.do_log(x))
+                    .cloned()
+                    .collect();
+                recycle(records);
+                Arc::new(data)
+            }
+            (None, Some(shared)) if append.has_filter() => Arc::new(
+                shared
+                    .iter()
+                    .filter(|x| append.do_log(x))
+                    .cloned()
+                    .collect(),
+            ),
+            (None, Some(shared)) => shared.clone(),
+            (None, None) => continue,
+        };
+        if !data.is_empty() {
+            worker.channel.send(data);
+        }
+    }
+    if let Some(shared) = shared {
+        recycle(shared);
+    }
+}
+
+/// the state of the running logger read by every `log` call,it is replaced as a whole
+struct Producer {
+    send: Sender<FastLogRecord>,
+    recv: Receiver<FastLogRecord>,
+    cfg: Arc<Config>,
+    shards: Option<Arc<ProducerShards>>,
+    /// the commands taken from the full channel by `OverflowPolicy::DropOldest`,
+    /// the format thread put them before the records still in the channel
+    evicted: Arc<Mutex<Vec<FastLogRecord>>>,
+}
+
+/// the counters of the producer threads in a shard,summed by `fastlog::stats`
+#[derive(Default)]
+struct ProducerCounters {
+    accepted: AtomicU64,
+    filtered: AtomicU64,
+}
+
+/// the producer shards of `Config::sharded`.
+/// a full shard send its batch while it is locked,so the batches of a shard are in order
+struct ProducerShards {
+    shards: Vec<Mutex<Vec<FastLogRecord>>>,
+    batch_len: usize,
+    max_staleness: Duration,
+    send: Sender<Vec<FastLogRecord>>,
+    recv: Receiver<Vec<FastLogRecord>>,
+}
+
+impl ProducerShards {
+    fn new(cfg: ShardConfig, chan_len: Option<usize>) -> Self {
+        let (send, recv) = chan(chan_len.map(|v| (v / cfg.batch_len).max(1)));
+        Self {
+            shards: (0..cfg.shards.max(1))
+                .map(|_| Mutex::new(Vec::with_capacity(cfg.batch_len)))
+                .collect(),
+            batch_len: cfg.batch_len,
+            max_staleness: cfg.max_staleness,
+            send,
+            recv,
+        }
+    }
+
+    /// push the record into the shard of this thread,return false if the format thread exit
+    fn push(&self, record: FastLogRecord) -> bool {
+        let index = SHARD.with(|v| *v) % self.shards.len();
+        let mut shard = self.shards[index].lock();
+        shard.push(record);
+        if shard.len() >= self.batch_len {
+            let batch = std::mem::replace(&mut *shard, Vec::with_capacity(self.batch_len));
+            return self.send.send(batch).is_ok();
+        }
+        true
+    }
+
+    /// hand off all shards before send a flush or exit command,the deadline is used by the panic hook
+    fn push_all(&self, deadline: Option<Instant>) {
+        for shard in &self.shards {
+            let mut shard = shard.lock();
+            if shard.is_empty() {
+                continue;
+            }
+            let batch = std::mem::take(&mut *shard);
+            match deadline {
+                Some(deadline) => {
+                    let _ = self.send.send_deadline(batch, deadline);
+                }
+                None => {
+                    let _ = self.send.send(batch);
+                }
+            }
+        }
+    }
+
+    /// wait the main channel,the batches or the staleness of shards,then receive them.
+    /// the main channel records(commands) are put last,the shards are handed off before a command is sent
+    fn recv(
+        &self,
+        recv: &Receiver<FastLogRecord>,
+        remain: &mut Vec<FastLogRecord>,
+        last_drain: &mut Instant,
+    ) {
+        if recv.is_empty() && self.recv.is_empty() {
+            let mut select = crossbeam_channel::Select::new();
+            select.recv(recv);
+            select.recv(&self.recv);
+            let _ = select.ready_timeout(self.max_staleness.saturating_sub(last_drain.elapsed()));
+        }
+        let mut commands = Vec::with_capacity(recv.len());
+        match recv.try_recv() {
+            Ok(v) => commands.push(v),
+            Err(crossbeam_channel::TryRecvError::Disconnected) => {
+                //all sender dropped,make appenders exit
+                commands.push(FastLogRecord::new_command(Command::CommandExit));
+            }
+            Err(crossbeam_channel::TryRecvError::Empty) => {}
+        }
+        for _ in 0..recv.len() {
+            match recv.try_recv() {
+                Ok(v) => commands.push(v),
+                Err(_) => break,
+            }
+        }
+        self.recv_batches(remain);
+        if last_drain.elapsed() >= self.max_staleness {
+            *last_drain = Instant::now();
+            //a locked shard is sending its batch,take it next time
+            for shard in &self.shards {
+                if let Some(mut shard) = shard.try_lock() {
+                    self.recv_batches(remain);
+                    remain.append(&mut shard);
+                }
+            }
+        }
+        remain.append(&mut commands);
+    }
+
+    fn recv_batches(&self, remain: &mut Vec<FastLogRecord>) {
+        for _ in 0..self.recv.len() {
+            match self.recv.try_recv() {
+                Ok(batch) => remain.extend(batch),
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+/// the format workers of `Config::format_workers`,the format thread is one of them.
+/// a batch is split into chunks,and the chunks are joined in order
+struct FormatPool {
+    workers: usize,
+    /// None if there is only the format thread
+    jobs: Option<Sender<FormatJob>>,
+    handles: Vec<JoinHandle<()>>,
+}
+
+/// a chunk of records,`format` is the index of an appender with own format,None means `Config::format`
+struct FormatJob {
+    cfg: Arc<Config>,
+    format: Option<usize>,
+    index: usize,
+    records: Vec<FastLogRecord>,
+    done: Sender<FormatDone>,
+}
+
+/// a chunk formatted by a worker,with the panic message if the format panicked
+type FormatDone = (usize, Vec<FastLogRecord>, Option<String>);
+
+impl FormatPool {
+    /// the worker threads exit when the pool is dropped or joined
+    fn new(workers: usize) -> Self {
+        if workers <= 1 {
+            return Self {
+                workers: 1,
+                jobs: None,
+                handles: vec![],
+            };
+        }
+        let (s, r) = chan::<FormatJob>(None);
+        let mut handles = Vec::with_capacity(workers - 1);
+        for _ in 1..workers {
+            let r = r.clone();
+            handles.push(spawn(move || {
+                LOGGER_THREAD.with(|v| v.set(true));
+                while let Ok(mut job) = r.recv() {
+                    //a panicked format return the chunk,so the format thread can handle it
+                    let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                        format_records(&job.cfg, job.format, &mut job.records)
+                    }));
+                    let panic = result.err().map(|e| panic_message(&*e));
+                    let _ = job.done.send((job.index, job.records, panic));
+                }
+            }));
+        }
+        Self {
+            workers,
+            jobs: Some(s),
+            handles,
+        }
+    }
+
+    /// stop the worker threads and wait them exit before the deadline,return the workers not exit
+    fn join(mut self, deadline: Option<Instant>) -> Vec<String> {
+        self.jobs.take();
+        let mut undrained = vec![];
+        for (index, handle) in self.handles.drain(..).enumerate() {
+            if join_deadline(handle, deadline).is_none() {
+                undrained.push(format!("format worker {}", index + 1));
+            }
+        }
+        undrained
+    }
+
+    fn format(&self, cfg: &Arc<Config>, format: Option<usize>, records: &mut Vec<FastLogRecord>) {
+        let jobs = match &self.jobs {
+            Some(jobs) if records.len() >= FORMAT_CHUNK_MIN * 2 => jobs,
+            _ => {
+                format_records(cfg, format, records);
+                return;
+            }
+        };
+        let chunks = (records.len() / FORMAT_CHUNK_MIN).min(self.workers);
+        let size = records.len().div_ceil(chunks);
+        let (done, results) = chan(None);
+        let mut formatted = Vec::with_capacity(chunks);
+        //the format thread format the first chunk
+        let mut rest = records.split_off(size);
+        let mut index = 0;
+        let mut lens = vec![];
+        while !rest.is_empty() {
+            let tail = rest.split_off(size.min(rest.len()));
+            index += 1;
+            lens.push(rest.len());
+            let job = FormatJob {
+                cfg: cfg.clone(),
+                format,
+                index,
+                records: rest,
+                done: done.clone(),
+            };
+            if let Err(crossbeam_channel::SendError(mut job)) = jobs.send(job) {
+                format_records(cfg, format, &mut job.records);
+                formatted.push((job.index, job.records));
+            }
+            rest = tail;
+        }
+        drop(done);
+        format_records(cfg, format, records);
+        let mut lost = 0;
+        for (index, mut chunk, panic) in results.iter() {
+            if let Some(message) = panic {
+                cfg.report(Diagnostic::Panic {
+                    appender: "format worker".to_string(),
+                    message,
+                });
+                //format it again,if it panic again the records of the chunk are dropped but the commands
+                let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                    format_records(cfg, format, &mut chunk)
+                }));
+                if result.is_err() {
+                    let len = chunk.len();
+                    chunk.retain(|x| x.command != Command::CommandRecord);
+                    lost += (len - chunk.len()) as u64;
+                }
+            }
+            formatted.push((index, chunk));
+        }
+        //a chunk not returned is dropped
+        for (index, len) in lens.into_iter().enumerate() {
+            if !formatted.iter().any(|x| x.0 == index + 1) {
+                lost += len as u64;
+            }
+        }
+        if lost > 0 {
+            logger().dropped.fetch_add(lost, Ordering::Relaxed);
+            logger().dropped_total.fetch_add(lost, Ordering::Relaxed);
+        }
+        formatted.sort_by_key(|x| x.0);
+        for (_, mut x) in formatted {
+            records.append(&mut x);
+        }
+    }
+}
+
+/// format the records not formatted yet
+fn format_records(cfg: &Config, format: Option<usize>, records: &mut [FastLogRecord]) {
+    let append = format.and_then(|index| cfg.appends.iter().nth(index));
+    let format = match append.and_then(|x| x.format.as_deref()) {
+        Some(v) => v,
+        None => cfg.format.as_ref(),
+    };
+    for x in records {
+        if x.formated.is_empty() {
+            if x.formated.capacity() == 0 {
+                x.formated = buffer_pool().take();
+            }
+            format.do_format(x);
+        }
+    }
+}
+
+/// recycle the buffers of the records,if this is the last owner of the batch
+fn recycle(data: Arc<Vec<FastLogRecord>>) {
+    if let Some(records) = Arc::into_inner(data) {
+        for x in records {
+            buffer_pool().recycle(x);
+        }
+    }
+}
+
+/// the channel of an appender thread
+struct AppenderChannel {
+    name: String,
+    overflow: OverflowPolicy,
+    send: Sender<Arc<Vec<FastLogRecord>>>,
+    /// used by `OverflowPolicy::DropOldest` and the stats
+    recv: Receiver<Arc<Vec<FastLogRecord>>>,
+    /// the commands not sent because the channel is full
+    pending: Mutex<Vec<FastLogRecord>>,
+    /// records dropped by the overflow
+    dropped: AtomicU64,
+    /// counters updated by the appender thread
+    batches: AtomicU64,
+    bytes: AtomicU64,
+    last_error: Mutex<Option<String>>,
+    alive: AtomicBool,
+    healthy: AtomicBool,
+    panics: AtomicU64,
+    /// the last ERROR batch of `OverflowPolicy::ShedByLevel` timed out
+    shed_timeout: AtomicBool,
+}
+
+impl AppenderChannel {
+    /// send the batch by the OverflowPolicy,so a stalled appender not block the others.
+    /// commands in a dropped batch are kept aside,a command never block the format thread(but `OverflowPolicy::Block`)
+    fn send(&self, data: Arc<Vec<FastLogRecord>>) {
+        let data = self.merge_pending(data);
+        let data = match self.overflow {
+            OverflowPolicy::Block => {
+                let _ = self.send.send(data);
+                return;
+            }
+            OverflowPolicy::BlockTimeout(timeout) => match self.send.send_timeout(data, timeout) {
+                Err(crossbeam_channel::SendTimeoutError::Timeout(v)) => v,
+                _ => return,
+            },
+            OverflowPolicy::DropNewest => match self.send.try_send(data) {
+                Err(crossbeam_channel::TrySendError::Full(v)) => v,
+                _ => return,
+            },
+            OverflowPolicy::DropOldest => {
+                let mut data = data;
+                while let Err(crossbeam_channel::TrySendError::Full(v)) = self.send.try_send(data) {
+                    data = v;
+                    if let Ok(oldest) = self.recv.try_recv() {
+                        self.drop_batch(oldest);
+                    }
+                }
+                self.wake();
+                return;
+            }
+            OverflowPolicy::ShedByLevel => {
+                let keep = match self.send.capacity() {
+                    Some(cap) if self.send.len() >= cap => log::Level::Error,
+                    Some(cap) if self.send.len() * 2 >= cap => log::Level::Info,
+                    _ => log::Level::Trace,
+                };
+                if keep == log::Level::Trace {
+                    self.shed_timeout.store(false, Ordering::Relaxed);
+                    let _ = self.send.send(data);
+                    return;
+                }
+                let mut records = Vec::with_capacity(data.len());
+                let mut errors = false;
+                for x in data.iter() {
+                    if x.command != Command::CommandRecord {
+                        records.push(x.clone());
+                    } else if x.level <= keep {
+                        errors |= x.level == log::Level::Error;
+                        records.push(x.clone());
+                    } else {
+                        self.dropped.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+                recycle(data);
+                let records = Arc::new(records);
+                //ERROR waits a while,the others are dropped when the channel is full
+                if errors || keep == log::Level::Info {
+                    let timeout = match self.shed_timeout.load(Ordering::Relaxed) {
+                        true => Duration::ZERO,
+                        false => SHED_ERROR_TIMEOUT,
+                    };
+                    match self.send.send_timeout(records, timeout) {
+                        Err(crossbeam_channel::SendTimeoutError::Timeout(v)) => {
+                            self.shed_timeout.store(true, Ordering::Relaxed);
+                            v
+                        }
+                        _ => {
+                            self.shed_timeout.store(false, Ordering::Relaxed);
+                            return;
+                        }
+                    }
+                } else {
+                    match self.send.try_send(records) {
+                        Err(crossbeam_channel::TrySendError::Full(v)) => v,
+                        _ => return,
+                    }
+                }
+            }
+        };
+        self.drop_batch(data);
+        self.wake();
+    }
+
+    /// send a command without blocking,it is kept aside when the channel is full
+    fn send_command(&self, command: FastLogRecord) {
+        let data = self.merge_pending(Arc::new(vec![command]));
+        if let Err(crossbeam_channel::TrySendError::Full(v)) = self.send.try_send(data) {
+            self.drop_batch(v);
+        }
+        self.wake();
+    }
+
+    /// put the pending commands before the batch,they are sent before the records sent after them
+    fn merge_pending(&self, data: Arc<Vec<FastLogRecord>>) -> Arc<Vec<FastLogRecord>> {
+        let mut records = {
+            let mut pending = self.pending.lock();
+            if pending.is_empty() {
+                return data;
+            }
+            std::mem::take(&mut *pending)
+        };
+        records.extend(data.iter().cloned());
+        recycle(data);
+        Arc::new(records)
+    }
+
+    /// count the records of the batch as dropped,and keep the commands of it aside
+    fn drop_batch(&self, data: Arc<Vec<FastLogRecord>>) {
+        {
+            let mut pending = self.pending.lock();
+            for x in data.iter() {
+                if x.command == Command::CommandRecord {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                } else {
+                    pending.push(x.clone());
+                }
+            }
+        }
+        recycle(data);
+    }
+
+    /// wake the appender thread for the pending commands,a full channel wake it anyway
+    fn wake(&self) {
+        if !self.pending.lock().is_empty() {
+            let _ = self.send.try_send(Arc::new(vec![]));
+        }
+    }
+
+    /// take the pending commands when the channel is empty,so they are after the batches sent before them
+    fn take_pending(&self) -> Option<Arc<Vec<FastLogRecord>>> {
+        let mut pending = self.pending.lock();
+        if pending.is_empty() || !self.send.is_empty() {
+            return None;
+        }
+        Some(Arc::new(std::mem::take(&mut *pending)))
+    }
+
+    fn stats(&self) -> AppenderStats {
+        AppenderStats {
+            name: self.name.clone(),
+            queue_len: self.send.len(),
+            queue_cap: self.send.capacity(),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            batches: self.batches.load(Ordering::Relaxed),
+            bytes: self.bytes.load(Ordering::Relaxed),
+            last_error: self.last_error.lock().clone(),
+            alive: self.alive.load(Ordering::Relaxed),
+            healthy: self.healthy.load(Ordering::Relaxed),
+            panics: self.panics.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// mark the appender not alive when the appender thread exit or panic
+struct AliveGuard(Arc<AppenderChannel>);
+
+impl Drop for AliveGuard {
+    fn drop(&mut self) {
+        self.0.alive.store(false, Ordering::Relaxed);
+    }
+}
+
+/// an appender thread
+struct AppenderWorker {
+    channel: Arc<AppenderChannel>,
+    handle: JoinHandle<()>,
+}
+
+/// spawn one thread for every appender of the config
+fn spawn_appenders(cfg: &Arc<Config>) -> Vec<AppenderWorker> {
+    let mut appenders = Vec::with_capacity(cfg.appends.len());
+    for (index, append) in cfg.appends.iter().enumerate() {
+        let (s, receiver) = chan::<Arc<Vec<FastLogRecord>>>(append.chan_len.or(cfg.chan_len));
+        let channel = Arc::new(AppenderChannel {
+            name: append.name.clone(),
+            overflow: append.overflow.unwrap_or(cfg.overflow),
+            send: s,
+            recv: receiver.clone(),
+            pending: Mutex::new(vec![]),
+            dropped: AtomicU64::new(0),
+            batches: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+            last_error: Mutex::new(None),
+            alive: AtomicBool::new(true),
+            healthy: AtomicBool::new(true),
+            panics: AtomicU64::new(0),
+            shed_timeout: AtomicBool::new(false),
+        });
+        let cfg = cfg.clone();
+        let guard = AliveGuard(channel.clone());
+        let handle = spawn(move || {
+            let guard = guard;
+            LOGGER_THREAD.with(|v| v.set(true));
+            let appender = match cfg.appends.iter().nth(index) {
+                Some(v) => v,
+                None => {
+                    return;
+                }
+            };
             let mut exit = false;
+            let mut last_restart = None;
             loop {
                 let mut remain = vec![];
-                if receiver.len() == 0 {
-                    if let Ok(msg) = receiver.recv() {
-                        remain.push(msg);
+                if receiver.is_empty() {
+                    match guard.0.take_pending() {
+                        Some(msg) => remain.push(msg),
+                        None => {
+                            let next_tick = match guard.0.healthy.load(Ordering::Relaxed) {
+                                true => appender.appender.lock().next_tick(),
+                                false => None,
+                            };
+                            let msg = match next_tick {
+                                Some(timeout) => match receiver.recv_timeout(timeout) {
+                                    Err(crossbeam_channel::RecvTimeoutError::Timeout) => {
+                                        tick_appender(&cfg, appender, &guard.0);
+                                        continue;
+                                    }
+                                    v => v.ok(),
+                                },
+                                None => receiver.recv().ok(),
+                            };
+                            match msg {
+                                Some(msg) => {
+                                    remain.push(msg);
+                                }
+                                None => {
+                                    break;
+                                }
+                            }
+                        }
                     }
                 }
-                //recv all
-                loop {
+                //recv all,at most the batches in channel now
+                for _ in 0..receiver.len() {
                     match receiver.try_recv() {
                         Ok(v) => {
                             remain.push(v);
@@ -151,9 +981,60 @@ pub fn init(config: Config) -> Result<&'static Logger, LogError> {
                     }
                 }
                 //lock get appender
-                let mut shared_appender = appender.lock();
+                let mut shared_appender = appender.appender.lock();
                 for msg in remain {
-                    shared_appender.do_logs(msg.as_ref());
+                    //a wake up for the pending commands
+                    if msg.is_empty() {
+                        continue;
+                    }
+                    if !guard.0.healthy.load(Ordering::Relaxed) {
+                        restart_appender(
+                            &cfg,
+                            appender,
+                            &mut shared_appender,
+                            &guard.0,
+                            &mut last_restart,
+                        );
+                    }
+                    if !guard.0.healthy.load(Ordering::Relaxed) {
+                        fallback_logs(appender, &msg);
+                    } else {
+                        let result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                            shared_appender.do_logs(msg.as_ref())
+                        }));
+                        match result {
+                            Ok(_) => {
+                                let bytes: usize = msg.iter().map(|x| x.formated.len()).sum();
+                                guard.0.batches.fetch_add(1, Ordering::Relaxed);
+                                guard.0.bytes.fetch_add(bytes as u64, Ordering::Relaxed);
+                                //the appender may have written the records,the fallback is only for an unhealthy appender
+                                if let Some(error) = shared_appender.take_error() {
+                                    let LogError::E(e) = &error;
+                                    *guard.0.last_error.lock() = Some(e.clone());
+                                    cfg.report(Diagnostic::Error {
+                                        appender: appender.name.clone(),
+                                        error,
+                                    });
+                                }
+                            }
+                            Err(e) => {
+                                guard.0.healthy.store(false, Ordering::Relaxed);
+                                guard.0.panics.fetch_add(1, Ordering::Relaxed);
+                                cfg.report(Diagnostic::Panic {
+                                    appender: appender.name.clone(),
+                                    message: panic_message(e.as_ref()),
+                                });
+                                restart_appender(
+                                    &cfg,
+                                    appender,
+                                    &mut shared_appender,
+                                    &guard.0,
+                                    &mut last_restart,
+                                );
+                                fallback_logs(appender, &msg);
+                            }
+                        }
+                    }
                     for x in msg.iter() {
                         match x.command {
                             Command::CommandRecord => {}
@@ -164,122 +1045,387 @@ pub fn init(config: Config) -> Result<&'static Logger, LogError> {
                             Command::CommandFlush(_) => {
                                 continue;
                             }
+                            Command::CommandReconfigure(_) => {}
                         }
                     }
+                    recycle(msg);
                 }
                 if exit {
                     break;
                 }
             }
         });
+        appenders.push(AppenderWorker { channel, handle });
     }
-    let sender_vec = Arc::new(sender_vec);
-    for _ in 0..1 {
-        let senders = sender_vec.clone();
-        spawn(move || {
-            loop {
-                if let Some(recv) = logger().recv.get() {
-                    let mut remain = Vec::with_capacity(recv.len());
-                    //recv
-                    if recv.len() == 0 {
-                        if let Ok(item) = recv.recv() {
-                            remain.push(item);
-                        }
-                    }
-                    //merge log
-                    loop {
-                        match recv.try_recv() {
-                            Ok(v) => {
-                                remain.push(v);
-                            }
-                            Err(_) => {
-                                break;
-                            }
-                        }
-                    }
-                    let mut exit = false;
-                    for x in &mut remain {
-                        if x.formated.is_empty() {
-                            logger()
-                                .cfg
-                                .get()
-                                .expect("logger cfg is none")
-                                .format
-                                .do_format(x);
-                        }
-                        if x.command.eq(&Command::CommandExit) {
-                            exit = true;
-                        }
-                    }
-                    let data = Arc::new(remain);
-                    for x in senders.iter() {
-                        let _ = x.send(data.clone());
-                    }
-                    if exit {
-                        break;
-                    }
-                } else {
-                    break;
-                }
+    appenders
+}
+
+/// call `LogAppender::tick`,a panic makes the appender unhealthy like `do_logs`
+fn tick_appender(cfg: &Config, appender: &AppenderConfig, channel: &AppenderChannel) {
+    let mut shared_appender = appender.appender.lock();
+    match std::panic::catch_unwind(AssertUnwindSafe(|| shared_appender.tick())) {
+        Ok(_) => {
+            if let Some(error) = shared_appender.take_error() {
+                let LogError::E(e) = &error;
+                *channel.last_error.lock() = Some(e.clone());
+                cfg.report(Diagnostic::Error {
+                    appender: appender.name.clone(),
+                    error,
+                });
             }
-        });
+        }
+        Err(e) => {
+            channel.healthy.store(false, Ordering::Relaxed);
+            channel.panics.fetch_add(1, Ordering::Relaxed);
+            cfg.report(Diagnostic::Panic {
+                appender: appender.name.clone(),
+                message: panic_message(e.as_ref()),
+            });
+        }
     }
-    return Ok(logger());
 }
 
-pub fn exit() -> Result<(), LogError> {
-    let fastlog_record = FastLogRecord {
-        command: Command::CommandExit,
-        level: log::Level::Info,
-        target: String::new(),
-        args: String::new(),
-        module_path: String::new(),
-        file: String::new(),
-        line: None,
-        now: SystemTime::now(),
-        formated: String::new(),
+/// replace the panicked appender by `AppenderConfig::restart`,at most once per `RESTART_INTERVAL`
+fn restart_appender(
+    cfg: &Config,
+    appender: &AppenderConfig,
+    shared_appender: &mut Box<dyn LogAppender>,
+    channel: &AppenderChannel,
+    last_restart: &mut Option<Instant>,
+) {
+    let factory = match &appender.restart {
+        Some(v) => v,
+        None => return,
     };
-    let result = logger()
-        .send
-        .get()
-        .ok_or_else(|| LogError::from("not init"))?
-        .send(fastlog_record);
-    match result {
-        Ok(()) => {
-            return Ok(());
+    if let Some(last) = last_restart {
+        if last.elapsed() < RESTART_INTERVAL {
+            return;
         }
-        _ => {}
     }
-    return Err(LogError::E("[fastlog] exit fail!".to_string()));
+    *last_restart = Some(Instant::now());
+    match std::panic::catch_unwind(AssertUnwindSafe(factory)) {
+        Ok(Ok(v)) => {
+            *shared_appender = v;
+            channel.healthy.store(true, Ordering::Relaxed);
+            cfg.report(Diagnostic::Restarted {
+                appender: appender.name.clone(),
+            });
+        }
+        Ok(Err(error)) => cfg.report(Diagnostic::Error {
+            appender: appender.name.clone(),
+            error,
+        }),
+        Err(e) => cfg.report(Diagnostic::Panic {
+            appender: appender.name.clone(),
+            message: panic_message(e.as_ref()),
+        }),
+    }
 }
 
-pub fn flush() -> Result<WaitGroup, LogError> {
-    let wg = WaitGroup::new();
-    let fastlog_record = FastLogRecord {
-        command: Command::CommandFlush(wg.clone()),
-        level: log::Level::Info,
-        target: String::new(),
-        args: String::new(),
-        module_path: String::new(),
-        file: String::new(),
-        line: None,
-        now: SystemTime::now(),
-        formated: String::new(),
+/// write the records by `AppenderConfig::fallback`,the records are lost if there is no fallback
+fn fallback_logs(appender: &AppenderConfig, records: &[FastLogRecord]) {
+    if let Some(fallback) = &appender.fallback {
+        let mut fallback = fallback.lock();
+        let _ = std::panic::catch_unwind(AssertUnwindSafe(|| fallback.do_logs(records)));
+    }
+}
+
+/// the message of a panic payload
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    match payload.downcast_ref::<&str>() {
+        Some(v) => v.to_string(),
+        None => match payload.downcast_ref::<String>() {
+            Some(v) => v.clone(),
+            None => "Box<dyn Any>".to_string(),
+        },
+    }
+}
+
+/// send exit to the appender threads and wait them write all records before the deadline,
+/// return the appenders not exit(for example stalled),their threads are left running
+fn exit_appenders(appenders: Vec<AppenderWorker>, deadline: Option<Instant>) -> Vec<String> {
+    for worker in &appenders {
+        worker
+            .channel
+            .send_command(FastLogRecord::new_command(Command::CommandExit));
+    }
+    let mut undrained = vec![];
+    for worker in appenders {
+        let name = worker.channel.name.clone();
+        if join_deadline(worker.handle, deadline).is_none() {
+            undrained.push(name);
+        }
+    }
+    undrained
+}
+
+/// install the panic hook once,the previous hook run after the panic is logged
+fn set_panic_hook() {
+    if PANIC_HOOK_SET.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        log_panic(info);
+        previous(info);
+    }));
+}
+
+/// send the panic record and flush,both bounded by `PANIC_FLUSH_TIMEOUT`
+/// so a panic inside the logger threads can not hang the process
+fn log_panic(info: &std::panic::PanicHookInfo<'_>) {
+    //the panic may be raised while a lock of the logger is held,so only the snapshot is read
+    let producer = logger().producer.load_full();
+    let capture = match &producer {
+        Some(producer) => producer.cfg.capture_panics,
+        None => false,
+    };
+    if !capture || log::Level::Error > logger().get_level() {
+        return;
+    }
+    let payload = panic_message(info.payload());
+    let thread = std::thread::current();
+    let thread = thread.name().unwrap_or("<unnamed>");
+    let backtrace = std::backtrace::Backtrace::force_capture();
+    let (file, line) = match info.location() {
+        Some(v) => (v.file(), Some(v.line())),
+        None => ("", None),
     };
-    let result = logger()
+    let record = FastLogRecord::from(
+        &Record::builder()
+            .level(log::Level::Error)
+            .target("panic")
+            .module_path(Some("panic"))
+            .file(Some(file))
+            .line(line)
+            .args(format_args!(
+                "thread '{}' panicked at {}:{}: {}\nstack backtrace:\n{}",
+                thread,
+                file,
+                line.unwrap_or_default(),
+                payload,
+                backtrace
+            ))
+            .build(),
+    );
+    let deadline = Instant::now() + PANIC_FLUSH_TIMEOUT;
+    let wg = WaitGroup::new();
+    if let Some(producer) = &producer {
+        if producer.send.send_deadline(record, deadline).is_err() {
+            return;
+        }
+        //the logger threads can not flush while panicking,the appender threads catch the panic
+        if LOGGER_THREAD.with(|v| v.get()) {
+            return;
+        }
+        if let Some(shards) = &producer.shards {
+            shards.push_all(Some(deadline));
+        }
+        let flush = FastLogRecord::new_command(Command::CommandFlush(wg.clone()));
+        if producer.send.send_deadline(flush, deadline).is_err() {
+            return;
+        }
+    }
+    //WaitGroup::wait has no timeout,wait it in another thread
+    let (done, wait) = chan::<()>(Some(1));
+    let spawned = std::thread::Builder::new().spawn(move || {
+        wg.wait();
+        let _ = done.send(());
+    });
+    if spawned.is_ok() {
+        let _ = wait.recv_deadline(deadline);
+    }
+}
+
+/// make the appender channels visible to `fastlog::appender_stats`,if the format thread is not stale
+fn publish_channels(epoch: u64, appenders: &[AppenderWorker]) {
+    let mut channels = logger().channels.write();
+    if logger().epoch.load(Ordering::SeqCst) == epoch {
+        *channels = appenders.iter().map(|x| x.channel.clone()).collect();
+    }
+}
+
+/// join the thread,return None if the thread panic or not finish before deadline
+fn join_deadline<T>(handle: JoinHandle<T>, deadline: Option<Instant>) -> Option<T> {
+    if let Some(deadline) = deadline {
+        while !handle.is_finished() {
+            if Instant::now() >= deadline {
+                return None;
+            }
+            std::thread::sleep(Duration::from_millis(1));
+        }
+    }
+    handle.join().ok()
+}
+
+/// replace the appenders,filters and format of the running logger.
+/// records sent before are written by the old appenders(with the old format and filters),and the old appenders are dropped when this returns.
+/// an old appender that can not write its records in `Config::drain_timeout` is left running and reported as `Diagnostic::Undrained`.
+/// the main channel is kept,so `Config::chan_len` only apply to the appender channels
+pub fn reconfigure(config: Config) -> Result<(), LogError> {
+    if config.appends.is_empty() {
+        return Err(LogError::from("[fastlog] appends can not be empty!"));
+    }
+    {
+        let send = logger().send.read();
+        let send = send.as_ref().ok_or_else(|| LogError::from("not init"))?;
+        if config.capture_panics {
+            set_panic_hook();
+        }
+        let level = config.max_level();
+        let cfg = Arc::new(config);
+        *logger().cfg.write() = Some(cfg.clone());
+        if let Some(producer) = logger().producer.load_full() {
+            logger().publish(Some(Producer {
+                send: producer.send.clone(),
+                recv: producer.recv.clone(),
+                cfg: cfg.clone(),
+                shards: producer.shards.clone(),
+                evicted: producer.evicted.clone(),
+            }));
+        }
+        logger().set_level(level);
+        //the format thread replace appenders at the marker
+        if let Some(shards) = logger().shards.read().as_ref() {
+            shards.push_all(None);
+        }
+        send.send(FastLogRecord::new_command(Command::CommandReconfigure(cfg)))
+            .map_err(|_| LogError::from("[fastlog] reconfigure fail!"))?;
+    }
+    flush()?.wait();
+    Ok(())
+}
+
+/// the result of `fastlog::shutdown`
+#[derive(Clone, Debug, Default)]
+pub struct ShutdownReport {
+    /// the appenders(and format workers) that not exit before the timeout(or panic)
+    pub undrained: Vec<String>,
+}
+
+impl ShutdownReport {
+    pub fn is_drained(&self) -> bool {
+        self.undrained.is_empty()
+    }
+}
+
+/// stop the logger,wait the format thread and appender threads write all records.
+/// timeout None means wait until all records are written.
+/// after shutdown,logs are ignored and `fastlog::init` can be called again
+pub fn shutdown(timeout: Option<Duration>) -> Result<ShutdownReport, LogError> {
+    let deadline = timeout.map(|v| Instant::now() + v);
+    let send = logger()
         .send
-        .get()
-        .ok_or_else(|| LogError::from("not init"))?
-        .send(fastlog_record);
-    match result {
-        Ok(()) => {
-            return Ok(wg);
+        .write()
+        .take()
+        .ok_or_else(|| LogError::from("not init"))?;
+    logger().publish(None);
+    if let Some(shards) = logger().shards.write().take() {
+        shards.push_all(None);
+    }
+    let _ = send.send(FastLogRecord::new_command(Command::CommandExit));
+    drop(send);
+    //the last appender thread drop the config and appenders
+    let cfg = {
+        let mut cfg_lock = logger().cfg.write();
+        logger().epoch.fetch_add(1, Ordering::SeqCst);
+        cfg_lock.take()
+    };
+    logger().recv.write().take();
+    logger().channels.write().clear();
+    let handle = logger().handle.lock().take();
+    let mut report = ShutdownReport::default();
+    match handle.and_then(|v| join_deadline(v, deadline)) {
+        Some((appenders, pool)) => {
+            drop(cfg);
+            for worker in appenders {
+                let name = worker.channel.name.clone();
+                if join_deadline(worker.handle, deadline).is_none() {
+                    report.undrained.push(name);
+                }
+            }
+            report.undrained.extend(pool.join(deadline));
         }
-        _ => {}
+        None => {
+            if let Some(cfg) = cfg {
+                for x in cfg.appends.iter() {
+                    report.undrained.push(x.name.clone());
+                }
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// the stats of the running appenders,a stalled appender has a full channel and counts the dropped records
+/// for example:
+/// ```rust
+/// for x in fastlog::appender_stats() {
+///     if x.is_stalled() {
+///         println!("{} stalled,dropped {} records", x.name, x.dropped);
+///     }
+/// }
+/// ```
+pub fn appender_stats() -> Vec<AppenderStats> {
+    logger().channels.read().iter().map(|x| x.stats()).collect()
+}
+
+/// a snapshot of the logger queue,counters and appender threads,for example export to metrics:
+/// ```rust
+/// let stats = fastlog::stats();
+/// if !stats.is_healthy() || stats.dropped > 0 {
+///     println!("logging falls behind: {:?}", stats);
+/// }
+/// ```
+pub fn stats() -> LogStats {
+    let (queue_len, queue_cap) = match logger().recv.read().as_ref() {
+        Some(recv) => (recv.len(), recv.capacity()),
+        None => (0, None),
+    };
+    LogStats {
+        queue_len,
+        queue_cap,
+        accepted: logger()
+            .counters
+            .iter()
+            .map(|v| v.accepted.load(Ordering::Relaxed))
+            .sum(),
+        filtered: logger()
+            .counters
+            .iter()
+            .map(|v| v.filtered.load(Ordering::Relaxed))
+            .sum(),
+        dropped: logger().dropped_total.load(Ordering::Relaxed),
+        running: match logger().handle.lock().as_ref() {
+            Some(handle) => !handle.is_finished(),
+            None => false,
+        },
+        appenders: appender_stats(),
+    }
+}
+
+/// stop the logger and wait all records are written,same as `fastlog::shutdown(None)`
+pub fn exit() -> Result<(), LogError> {
+    shutdown(None)?;
+    Ok(())
+}
+
+pub fn flush() -> Result<WaitGroup, LogError> {
+    let wg = WaitGroup::new();
+    let fastlog_record = FastLogRecord::new_command(Command::CommandFlush(wg.clone()));
+    let send = logger().send.read();
+    let send = send.as_ref().ok_or_else(|| LogError::from("not init"))?;
+    if let Some(shards) = logger().shards.read().as_ref() {
+        shards.push_all(None);
+    }
+    let result = send.send(fastlog_record);
+    if let Ok(()) = result {
+        return Ok(wg);
     }
-    return Err(LogError::E("[fastlog] flush fail!".to_string()));
+    Err(LogError::E("[fastlog] flush fail!".to_string()))
 }
 
+#[allow(clippy::result_large_err)]
 pub fn print(log: String) -> Result<(), SendError<FastLogRecord>> {
     logger().print(log)
 }
@@ -1,34 +1,52 @@
+use crate::appender::FastLogRecord;
 use dark_std::sync::SyncVec;
 
 ///log filter
 pub trait Filter: Send + Sync {
     /// if return true=do_log/false=not_log
     fn do_log(&self, record: &log::Record) -> bool;
+
+    /// filter an already captured record,for example the filters of an appender.
+    /// default rebuild an `log::Record` and call `do_log`
+    fn do_log_record(&self, record: &FastLogRecord) -> bool {
+        self.do_log(
+            &log::Record::builder()
+                .level(record.level)
+                .target(&record.target)
+                .module_path(Some(record.module_path.as_ref()))
+                .file(Some(record.file.as_ref()))
+                .line(record.line)
+                .key_values(record)
+                .args(format_args!("{}", record.args))
+                .build(),
+        )
+    }
 }
 
 /// an Module Filter
 /// ```rust
-/// fn main(){
-///    use fastlog::Config;
-///    use fastlog::filter::ModuleFilter;
-///    let filter = ModuleFilter::new();
-///    filter.modules.push(module_path!().to_string());
-///    fastlog::init(Config::new().console().add_filter(filter)).unwrap();
-/// }
+/// use fastlog::Config;
+/// use fastlog::filter::ModuleFilter;
+/// let filter = ModuleFilter::new();
+/// filter.modules.push(module_path!().to_string());
+/// fastlog::init(Config::new().console().add_filter(filter)).unwrap();
 /// ```
 pub struct ModuleFilter {
     pub modules: SyncVec<String>,
 }
 
+impl Default for ModuleFilter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ModuleFilter {
     pub fn new() -> Self {
         Self { modules: SyncVec::new() }
     }
-}
 
-impl Filter for ModuleFilter {
-    fn do_log(&self, record: &log::Record) -> bool {
-        let module = record.module_path().unwrap_or("");
+    fn allow(&self, module: &str) -> bool {
         if !self.modules.is_empty() {
             for x in self.modules.iter() {
                 if module == x {
@@ -36,6 +54,16 @@ impl Filter for ModuleFilter {
                 }
             }
         }
-        return true;
+        true
+    }
+}
+
+impl Filter for ModuleFilter {
+    fn do_log(&self, record: &log::Record) -> bool {
+        self.allow(record.module_path().unwrap_or(""))
+    }
+
+    fn do_log_record(&self, record: &FastLogRecord) -> bool {
+        self.allow(&record.module_path)
     }
 }
@@ -1,37 +1,54 @@
-use crate::appender::{Command, FastLogRecord, RecordFormat};
+use crate::appender::{Command, FastLogRecord, FieldValue, RecordFormat};
+use crate::error::LogError;
 use log::LevelFilter;
+use std::fmt::Write;
+use std::ops::Range;
 
+#[derive(Default)]
 pub enum TimeType {
+    #[default]
     Local,
-    //default
     Utc,
 }
 
-impl Default for TimeType {
-    fn default() -> Self {
-        TimeType::Local
-    }
-}
-
 pub struct FastLogFormat {
     // show line level
     pub display_line_level: LevelFilter,
     pub time_type: TimeType,
 }
 
+/// `YYYY-MM-DD hh:mm:ss.000`
+fn display_ms(now: &fastdate::DateTime) -> String {
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02}.{:03}",
+        now.year(),
+        now.mon(),
+        now.day(),
+        now.hour(),
+        now.minute(),
+        now.sec(),
+        now.nano() / 1_000_000
+    )
+}
+
 impl RecordFormat for FastLogFormat {
     fn do_format(&self, arg: &mut FastLogRecord) {
         match &arg.command {
             Command::CommandRecord => {
                 let now = match self.time_type {
-                    TimeType::Local => fastdate::DateTime::from(arg.now)
-                        .set_offset(fastdate::offset_sec())
-                        .display_stand_ms(),
-                    TimeType::Utc => fastdate::DateTime::from(arg.now).display_stand_ms(),
+                    TimeType::Local => display_ms(
+                        &fastdate::DateTime::from(arg.now).set_offset(fastdate::offset_sec()),
+                    ),
+                    TimeType::Utc => display_ms(&fastdate::DateTime::from(arg.now)),
                 };
+                //write into the buffer,it may be taken from BufferPool
+                arg.formated.clear();
+                let start = now.len() + 1;
+                arg.level_span = Some(start..start + arg.level.as_str().len());
                 if arg.level.to_level_filter() <= self.display_line_level {
-                    arg.formated = format!(
-                        "{} {} [{}:{}] {}\n",
+                    let _ = write!(
+                        arg.formated,
+                        "{} {} [{}:{}] {}",
                         &now,
                         arg.level,
                         arg.file,
@@ -39,15 +56,27 @@ impl RecordFormat for FastLogFormat {
                         arg.args,
                     );
                 } else {
-                    arg.formated = format!("{} {} {}\n", &now, arg.level, arg.args);
+                    let _ = write!(arg.formated, "{} {} {}", &now, arg.level, arg.args);
+                }
+                for (k, v) in &arg.fields {
+                    arg.formated.push(' ');
+                    write_kv(&mut arg.formated, k, v);
                 }
+                arg.formated.push('\n');
             }
             Command::CommandExit => {}
             Command::CommandFlush(_) => {}
+            Command::CommandReconfigure(_) => {}
         }
     }
 }
 
+impl Default for FastLogFormat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl FastLogFormat {
     pub fn new() -> FastLogFormat {
         Self {
@@ -69,15 +98,113 @@ impl FastLogFormat {
     }
 }
 
+/// a member of `FastLogFormatJson`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JsonField {
+    Args,
+    Date,
+    File,
+    Level,
+    Line,
+    Target,
+    ModulePath,
+    /// the thread name,or the thread id when the thread has no name
+    Thread,
+    /// all key-values which are not chosen by `JsonField::Field`,
+    /// they are flat members when the key is empty,or nested in an object of the key.
+    /// a flat key-value with the key of another member is written as `fields.<key>`,so the keys are unique
+    Fields,
+    /// a single key-value,it is skipped when the record does not have it
+    Field(String),
+}
+
+impl JsonField {
+    /// the default key of member
+    pub fn key(&self) -> &str {
+        match self {
+            JsonField::Args => "args",
+            JsonField::Date => "date",
+            JsonField::File => "file",
+            JsonField::Level => "level",
+            JsonField::Line => "line",
+            JsonField::Target => "target",
+            JsonField::ModulePath => "module_path",
+            JsonField::Thread => "thread",
+            JsonField::Fields => "",
+            JsonField::Field(key) => key,
+        }
+    }
+}
+
+/// write a json string(RFC 8259) with the quotes.
+/// `"`,`\` and control chars are escaped,when `ascii` is true the non-ascii chars are escaped as `\uXXXX`(a surrogate pair above U+FFFF).
+/// a `str` is always valid UTF-8,so a lone surrogate can not reach the output unescaped
+pub fn write_json_str(out: &mut String, v: &str, ascii: bool) {
+    out.push('"');
+    let mut start = 0;
+    for (i, c) in v.char_indices() {
+        let escape = match c {
+            '"' => Some("\\\""),
+            '\\' => Some("\\\\"),
+            '\n' => Some("\\n"),
+            '\r' => Some("\\r"),
+            '\t' => Some("\\t"),
+            '\u{8}' => Some("\\b"),
+            '\u{c}' => Some("\\f"),
+            c if (c as u32) < 0x20 || (ascii && !c.is_ascii()) => None,
+            _ => continue,
+        };
+        out.push_str(&v[start..i]);
+        start = i + c.len_utf8();
+        match escape {
+            Some(escape) => out.push_str(escape),
+            None => {
+                let mut buf = [0u16; 2];
+                for unit in c.encode_utf16(&mut buf) {
+                    let _ = write!(out, "\\u{:04x}", unit);
+                }
+            }
+        }
+    }
+    out.push_str(&v[start..]);
+    out.push('"');
+}
+
+/// write a json value of key-value,a non finite float is `null`
+pub fn write_json_value(out: &mut String, v: &FieldValue, ascii: bool) {
+    match v {
+        FieldValue::Str(v) => write_json_str(out, v, ascii),
+        FieldValue::F64(v) if !v.is_finite() => out.push_str("null"),
+        _ => {
+            let _ = write!(out, "{}", v);
+        }
+    }
+}
+
+/// json line format,for example `{"args":"login","date":"2022-08-19 09:53:47.798","file":"src/main.rs","level":"INFO","line":21,"user_id":42}`
 pub struct FastLogFormatJson {
     pub time_type: TimeType,
+    /// the members in order,with their key
+    pub fields: Vec<(JsonField, String)>,
+    /// escape the non-ascii chars
+    pub ascii: bool,
 }
 
 impl Default for FastLogFormatJson {
     fn default() -> Self {
         Self {
             time_type: TimeType::default(),
+            fields: vec![],
+            ascii: false,
         }
+        .set_fields(vec![
+            JsonField::Args,
+            JsonField::Date,
+            JsonField::File,
+            JsonField::Level,
+            JsonField::Line,
+            JsonField::Fields,
+        ])
     }
 }
 
@@ -85,28 +212,16 @@ impl RecordFormat for FastLogFormatJson {
     fn do_format(&self, arg: &mut FastLogRecord) {
         match &arg.command {
             Command::CommandRecord => {
-                let now = match self.time_type {
-                    TimeType::Local => fastdate::DateTime::from(arg.now)
-                        .add_sub_sec(fastdate::offset_sec() as i64)
-                        .display_stand_ms(),
-                    TimeType::Utc => fastdate::DateTime::from(arg.now).display_stand_ms(),
-                };
-                //{"args":"Commencing yak shaving","date":"2022-08-19 09:53:47.798674","file":"example/src/split_log.rs","level":"INFO","line":21}
-                let args = arg.args.replace("\"", "\\\"");
-                let file = arg.file.replace("\\", "/");
-                arg.formated = format!(
-                    "{}\"args\":\"{}\",\"date\":\"{}\",\"file\":\"{}\",\"level\":\"{}\",\"line\":{}{}",
-                    "{",
-                    args,
-                    now,
-                    file,
-                    arg.level,
-                    arg.line.unwrap_or_default(),
-                    "}\n"
-                );
+                //write into the buffer,it may be taken from BufferPool
+                let mut out = std::mem::take(&mut arg.formated);
+                out.clear();
+                arg.level_span = self.write_members(&mut out, arg);
+                out.push('\n');
+                arg.formated = out;
             }
             Command::CommandExit => {}
             Command::CommandFlush(_) => {}
+            Command::CommandReconfigure(_) => {}
         }
     }
 }
@@ -115,4 +230,740 @@ impl FastLogFormatJson {
     pub fn new() -> FastLogFormatJson {
         Self::default()
     }
+
+    /// set time_type
+    pub fn set_time_type(mut self, time_type: TimeType) -> Self {
+        self.time_type = time_type;
+        self
+    }
+
+    /// choose the members in order,with the default keys
+    pub fn set_fields(mut self, fields: Vec<JsonField>) -> Self {
+        self.fields = fields
+            .into_iter()
+            .map(|v| {
+                let key = v.key().to_string();
+                (v, key)
+            })
+            .collect();
+        self
+    }
+
+    /// add a member with the default key,for example `.add_field(JsonField::Thread)`
+    pub fn add_field(self, field: JsonField) -> Self {
+        let key = field.key().to_string();
+        self.rename_field(field, &key)
+    }
+
+    /// remove a member
+    pub fn remove_field(mut self, field: &JsonField) -> Self {
+        self.fields.retain(|(v, _)| v != field);
+        self
+    }
+
+    /// set the key of a member,the member is added when it is not chosen.
+    /// for example `.rename_field(JsonField::Date, "@timestamp")`
+    pub fn rename_field(mut self, field: JsonField, key: &str) -> Self {
+        match self.fields.iter_mut().find(|(v, _)| *v == field) {
+            Some((_, v)) => *v = key.to_string(),
+            None => self.fields.push((field, key.to_string())),
+        }
+        self
+    }
+
+    /// nest the key-values in an object of the key,for example `{"args":"login",..,"fields":{"user_id":42}}`
+    pub fn nest_fields(self, key: &str) -> Self {
+        self.rename_field(JsonField::Fields, key)
+    }
+
+    /// escape the non-ascii chars as `\uXXXX`
+    pub fn set_ascii(mut self, ascii: bool) -> Self {
+        self.ascii = ascii;
+        self
+    }
+
+    /// write a record as a json object without the new line
+    pub fn write_record(&self, out: &mut String, arg: &FastLogRecord) {
+        self.write_members(out, arg);
+    }
+
+    /// write the json object,return the range of the level name
+    fn write_members(&self, out: &mut String, arg: &FastLogRecord) -> Option<Range<usize>> {
+        let mut level_span = None;
+        out.push('{');
+        let mut first = true;
+        for (field, key) in &self.fields {
+            match field {
+                JsonField::Fields => {
+                    let nested = !key.is_empty();
+                    if nested {
+                        self.write_key(out, &mut first, key);
+                        out.push('{');
+                    }
+                    let mut first_field = true;
+                    for (k, v) in &arg.fields {
+                        if self.is_chosen(k) {
+                            continue;
+                        }
+                        if nested {
+                            self.write_key(out, &mut first_field, k);
+                        } else if self.is_member(k) {
+                            self.write_key(out, &mut first, &format!("fields.{}", k));
+                        } else {
+                            self.write_key(out, &mut first, k);
+                        }
+                        write_json_value(out, v, self.ascii);
+                    }
+                    if nested {
+                        out.push('}');
+                    }
+                }
+                JsonField::Field(k) => {
+                    if let Some(v) = arg.field(k) {
+                        self.write_key(out, &mut first, key);
+                        write_json_value(out, v, self.ascii);
+                    }
+                }
+                JsonField::Line => {
+                    self.write_key(out, &mut first, key);
+                    let _ = write!(out, "{}", arg.line.unwrap_or_default());
+                }
+                JsonField::Date => {
+                    let now = match self.time_type {
+                        TimeType::Local => display_ms(
+                            &fastdate::DateTime::from(arg.now)
+                                .add_sub_sec(fastdate::offset_sec() as i64),
+                        ),
+                        TimeType::Utc => display_ms(&fastdate::DateTime::from(arg.now)),
+                    };
+                    self.write_key(out, &mut first, key);
+                    write_json_str(out, &now, self.ascii);
+                }
+                JsonField::Level => {
+                    self.write_key(out, &mut first, key);
+                    let start = out.len() + 1;
+                    write_json_str(out, arg.level.as_str(), self.ascii);
+                    level_span = Some(start..out.len() - 1);
+                }
+                JsonField::Thread => {
+                    self.write_key(out, &mut first, key);
+                    match arg.thread_name() {
+                        Some(name) => write_json_str(out, &name, self.ascii),
+                        None => out.push_str("null"),
+                    }
+                }
+                JsonField::Args => {
+                    self.write_key(out, &mut first, key);
+                    write_json_str(out, &arg.args, self.ascii);
+                }
+                JsonField::File => {
+                    self.write_key(out, &mut first, key);
+                    write_json_str(out, &arg.file, self.ascii);
+                }
+                JsonField::Target => {
+                    self.write_key(out, &mut first, key);
+                    write_json_str(out, &arg.target, self.ascii);
+                }
+                JsonField::ModulePath => {
+                    self.write_key(out, &mut first, key);
+                    write_json_str(out, &arg.module_path, self.ascii);
+                }
+            }
+        }
+        out.push('}');
+        level_span
+    }
+
+    fn write_key(&self, out: &mut String, first: &mut bool, key: &str) {
+        if !*first {
+            out.push(',');
+        }
+        *first = false;
+        write_json_str(out, key, self.ascii);
+        out.push(':');
+    }
+
+    /// the key-value is a single member
+    fn is_chosen(&self, key: &str) -> bool {
+        self.fields
+            .iter()
+            .any(|(v, _)| matches!(v, JsonField::Field(k) if k == key))
+    }
+
+    /// the key of a member,a flat key-value must not reuse it
+    fn is_member(&self, key: &str) -> bool {
+        self.fields
+            .iter()
+            .any(|(v, k)| *v != JsonField::Fields && k == key)
+    }
+}
+
+/// a date item of `%d{..}`
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum DateItem {
+    Literal(String),
+    Year,
+    Month,
+    Day,
+    Hour,
+    Minute,
+    Second,
+    /// the fraction of second with the digits,and whether it starts with a dot
+    Fraction(u32, bool),
+    /// the utc offset,`+0800` or `+08:00`
+    Offset(bool),
+    /// the unix timestamp in seconds
+    Unix,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum PatternKind {
+    Literal(String),
+    Date(Vec<DateItem>),
+    Level,
+    Args,
+    Thread,
+    Target,
+    ModulePath,
+    File,
+    Line,
+    Fields,
+    Field(String),
+    Newline,
+}
+
+/// padding and truncation of a placeholder,for example `%-5l` or `%.20M`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct PatternPad {
+    min: usize,
+    max: Option<usize>,
+    left: bool,
+    /// truncate the end instead of the start
+    truncate_end: bool,
+}
+
+/// a format compiled from a template,for example `PatternFormat::new("%d{%Y-%m-%dT%H:%M:%S%.3f} %-5l %t [%M] %m%n")`.
+///
+/// placeholders:
+/// * `%d` or `%d{..}` date,`%Y %m %d %H %M %S %.3f %.6f %.9f %f %z %:z %s %%` can be used in `{..}`,the default is `%Y-%m-%d %H:%M:%S%.3f`
+/// * `%l` or `%p` level
+/// * `%m` args
+/// * `%t` thread name,or the thread id when the thread has no name
+/// * `%T` or `%c` target
+/// * `%M` module_path
+/// * `%f` or `%F` file
+/// * `%L` line
+/// * `%X` all key-values as `k=v`,separated by a space
+/// * `%X{key}` the value of a key-value,it is empty when the record does not have it
+/// * `%n` new line
+/// * `%%` percent sign
+///
+/// a placeholder can be padded and truncated like logback,`%5l` pads left,`%-5l` pads right,
+/// `%.10m` keeps the last 10 chars and `%.-10m` keeps the first 10 chars
+pub struct PatternFormat {
+    pub time_type: TimeType,
+    pieces: Vec<(PatternKind, PatternPad)>,
+}
+
+impl PatternFormat {
+    /// compile the template,an unknown placeholder is an error
+    pub fn new(pattern: &str) -> Result<Self, LogError> {
+        let mut pieces = vec![];
+        let mut literal = String::new();
+        let mut chars = pattern.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                literal.push(c);
+                continue;
+            }
+            if chars.next_if_eq(&'%').is_some() {
+                literal.push('%');
+                continue;
+            }
+            let mut pad = PatternPad {
+                left: chars.next_if_eq(&'-').is_some(),
+                min: read_number(&mut chars).unwrap_or_default(),
+                ..Default::default()
+            };
+            if chars.next_if_eq(&'.').is_some() {
+                pad.truncate_end = chars.next_if_eq(&'-').is_some();
+                pad.max = Some(read_number(&mut chars).ok_or_else(|| {
+                    LogError::from(format!("pattern `{}` needs a number after `.`", pattern))
+                })?);
+            }
+            let conversion = chars.next().ok_or_else(|| {
+                LogError::from(format!("pattern `{}` ends with a placeholder", pattern))
+            })?;
+            //only `%d` and `%X` have an option,a `{` after the others is a literal
+            let option = if matches!(conversion, 'd' | 'X') && chars.next_if_eq(&'{').is_some() {
+                let mut option = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => option.push(c),
+                        None => {
+                            return Err(LogError::from(format!(
+                                "pattern `{}` has an unclosed `{{`",
+                                pattern
+                            )))
+                        }
+                    }
+                }
+                Some(option)
+            } else {
+                None
+            };
+            let kind = match conversion {
+                'd' => PatternKind::Date(compile_date(
+                    option.as_deref().unwrap_or("%Y-%m-%d %H:%M:%S%.3f"),
+                )?),
+                'l' | 'p' => PatternKind::Level,
+                'm' => PatternKind::Args,
+                't' => PatternKind::Thread,
+                'T' | 'c' => PatternKind::Target,
+                'M' => PatternKind::ModulePath,
+                'f' | 'F' => PatternKind::File,
+                'L' => PatternKind::Line,
+                'X' => match option {
+                    Some(key) => PatternKind::Field(key),
+                    None => PatternKind::Fields,
+                },
+                'n' => PatternKind::Newline,
+                _ => {
+                    return Err(LogError::from(format!(
+                        "pattern `{}` has an unknown placeholder `%{}`",
+                        pattern, conversion
+                    )))
+                }
+            };
+            if !literal.is_empty() {
+                pieces.push((
+                    PatternKind::Literal(std::mem::take(&mut literal)),
+                    PatternPad::default(),
+                ));
+            }
+            pieces.push((kind, pad));
+        }
+        if !literal.is_empty() {
+            pieces.push((PatternKind::Literal(literal), PatternPad::default()));
+        }
+        Ok(Self {
+            time_type: TimeType::default(),
+            pieces,
+        })
+    }
+
+    /// set time_type
+    pub fn set_time_type(mut self, time_type: TimeType) -> Self {
+        self.time_type = time_type;
+        self
+    }
+
+    fn write_piece(&self, out: &mut String, kind: &PatternKind, arg: &FastLogRecord) {
+        match kind {
+            PatternKind::Literal(v) => out.push_str(v),
+            PatternKind::Date(items) => {
+                let now = match self.time_type {
+                    TimeType::Local => {
+                        fastdate::DateTime::from(arg.now).set_offset(fastdate::offset_sec())
+                    }
+                    TimeType::Utc => fastdate::DateTime::from(arg.now),
+                };
+                for item in items {
+                    let _ = match item {
+                        DateItem::Literal(v) => write!(out, "{}", v),
+                        DateItem::Year => write!(out, "{:04}", now.year()),
+                        DateItem::Month => write!(out, "{:02}", now.mon()),
+                        DateItem::Day => write!(out, "{:02}", now.day()),
+                        DateItem::Hour => write!(out, "{:02}", now.hour()),
+                        DateItem::Minute => write!(out, "{:02}", now.minute()),
+                        DateItem::Second => write!(out, "{:02}", now.sec()),
+                        DateItem::Fraction(digits, dot) => write!(
+                            out,
+                            "{}{:0width$}",
+                            if *dot { "." } else { "" },
+                            now.nano() / 10u32.pow(9 - digits),
+                            width = *digits as usize
+                        ),
+                        DateItem::Offset(colon) => {
+                            let offset = match self.time_type {
+                                TimeType::Local => now.offset(),
+                                TimeType::Utc => 0,
+                            };
+                            let sign = if offset < 0 { '-' } else { '+' };
+                            let offset = offset.unsigned_abs() / 60;
+                            write!(
+                                out,
+                                "{}{:02}{}{:02}",
+                                sign,
+                                offset / 60,
+                                if *colon { ":" } else { "" },
+                                offset % 60
+                            )
+                        }
+                        DateItem::Unix => write!(out, "{}", now.unix_timestamp()),
+                    };
+                }
+            }
+            PatternKind::Level => out.push_str(arg.level.as_str()),
+            PatternKind::Args => out.push_str(&arg.args),
+            PatternKind::Thread => {
+                if let Some(name) = arg.thread_name() {
+                    out.push_str(&name);
+                }
+            }
+            PatternKind::Target => out.push_str(&arg.target),
+            PatternKind::ModulePath => out.push_str(&arg.module_path),
+            PatternKind::File => out.push_str(&arg.file),
+            PatternKind::Line => {
+                let _ = write!(out, "{}", arg.line.unwrap_or_default());
+            }
+            PatternKind::Fields => {
+                for (i, (k, v)) in arg.fields.iter().enumerate() {
+                    if i > 0 {
+                        out.push(' ');
+                    }
+                    write_kv(out, k, v);
+                }
+            }
+            PatternKind::Field(key) => {
+                if let Some(v) = arg.field(key) {
+                    let _ = write!(out, "{}", v);
+                }
+            }
+            PatternKind::Newline => out.push('\n'),
+        }
+    }
+}
+
+impl RecordFormat for PatternFormat {
+    fn do_format(&self, arg: &mut FastLogRecord) {
+        match &arg.command {
+            Command::CommandRecord => {
+                //write into the buffer,it may be taken from BufferPool
+                let mut out = std::mem::take(&mut arg.formated);
+                out.clear();
+                let mut level_span = None;
+                for (kind, pad) in &self.pieces {
+                    let start = out.len();
+                    self.write_piece(&mut out, kind, arg);
+                    if pad.min > 0 || pad.max.is_some() {
+                        pad_piece(&mut out, start, pad);
+                    }
+                    //the first `%l`,a truncated level name is not colored
+                    if *kind == PatternKind::Level && level_span.is_none() {
+                        let name = arg.level.as_str();
+                        level_span = out[start..]
+                            .find(name)
+                            .map(|at| start + at..start + at + name.len());
+                    }
+                }
+                arg.formated = out;
+                arg.level_span = level_span;
+            }
+            Command::CommandExit => {}
+            Command::CommandFlush(_) => {}
+            Command::CommandReconfigure(_) => {}
+        }
+    }
+}
+
+fn read_number(chars: &mut std::iter::Peekable<std::str::Chars>) -> Option<usize> {
+    let mut number = None;
+    while let Some(c) = chars.next_if(|c| c.is_ascii_digit()) {
+        number = Some(number.unwrap_or(0) * 10 + (c as usize - '0' as usize));
+    }
+    number
+}
+
+fn compile_date(pattern: &str) -> Result<Vec<DateItem>, LogError> {
+    let mut items = vec![];
+    let mut literal = String::new();
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            literal.push(c);
+            continue;
+        }
+        let item = match chars.next() {
+            Some('%') => {
+                literal.push('%');
+                continue;
+            }
+            Some('Y') => DateItem::Year,
+            Some('m') => DateItem::Month,
+            Some('d') => DateItem::Day,
+            Some('H') => DateItem::Hour,
+            Some('M') => DateItem::Minute,
+            Some('S') => DateItem::Second,
+            Some('f') => DateItem::Fraction(9, false),
+            Some('s') => DateItem::Unix,
+            Some('z') => DateItem::Offset(false),
+            Some(':') if chars.next_if_eq(&'z').is_some() => DateItem::Offset(true),
+            Some('.') => match read_number(&mut chars) {
+                Some(digits @ (3 | 6 | 9)) if chars.next_if_eq(&'f').is_some() => {
+                    DateItem::Fraction(digits as u32, true)
+                }
+                _ => {
+                    return Err(LogError::from(format!(
+                        "date `{}` supports `%.3f`,`%.6f` or `%.9f`",
+                        pattern
+                    )))
+                }
+            },
+            c => {
+                return Err(LogError::from(format!(
+                    "date `{}` has an unknown item `%{}`",
+                    pattern,
+                    c.map(String::from).unwrap_or_default()
+                )))
+            }
+        };
+        if !literal.is_empty() {
+            items.push(DateItem::Literal(std::mem::take(&mut literal)));
+        }
+        items.push(item);
+    }
+    if !literal.is_empty() {
+        items.push(DateItem::Literal(literal));
+    }
+    Ok(items)
+}
+
+/// pad or truncate `out[start..]` by chars
+fn pad_piece(out: &mut String, start: usize, pad: &PatternPad) {
+    let mut len = out[start..].chars().count();
+    if let Some(max) = pad.max {
+        if len > max {
+            if pad.truncate_end {
+                let end = out[start..]
+                    .char_indices()
+                    .nth(max)
+                    .map(|(i, _)| start + i)
+                    .unwrap_or(out.len());
+                out.truncate(end);
+            } else {
+                let end = out[start..]
+                    .char_indices()
+                    .nth(len - max)
+                    .map(|(i, _)| start + i)
+                    .unwrap_or(out.len());
+                out.drain(start..end);
+            }
+            len = max;
+        }
+    }
+    for _ in len..pad.min {
+        if pad.left {
+            out.push(' ');
+        } else {
+            out.insert(start, ' ');
+        }
+    }
+}
+
+/// write a key-value as `k=v`,a string with spaces,quotes or `=` is quoted
+pub(crate) fn write_kv(out: &mut String, k: &str, v: &FieldValue) {
+    let _ = match v {
+        FieldValue::Str(v)
+            if v.is_empty() || v.contains(|c: char| c.is_whitespace() || c == '"' || c == '=') =>
+        {
+            write!(out, "{}={:?}", k, v)
+        }
+        _ => write!(out, "{}={}", k, v),
+    };
+}
+
+/// the timestamp style of `LogfmtFormat`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimestampStyle {
+    /// `2024-01-01T00:00:00.000+08:00`,`Z` when time_type is utc
+    #[default]
+    Rfc3339,
+    /// `"2024-01-01 00:00:00.000"`,the same as `FastLogFormat`
+    Stand,
+    /// seconds since the unix epoch
+    UnixSecs,
+    /// milliseconds since the unix epoch
+    UnixMillis,
+}
+
+/// logfmt format,for example `ts=2024-01-01T00:00:00.000+08:00 level=info msg=login module=app user_id=42`.
+/// a value is quoted when it is empty or has spaces,`=`,`"` or control chars,a key drops these chars
+pub struct LogfmtFormat {
+    pub time_type: TimeType,
+    pub timestamp: TimestampStyle,
+    pub target: bool,
+    pub module_path: bool,
+    /// file and line
+    pub file: bool,
+    pub thread: bool,
+}
+
+impl Default for LogfmtFormat {
+    fn default() -> Self {
+        Self {
+            time_type: TimeType::default(),
+            timestamp: TimestampStyle::default(),
+            target: false,
+            module_path: true,
+            file: false,
+            thread: false,
+        }
+    }
+}
+
+impl RecordFormat for LogfmtFormat {
+    fn do_format(&self, arg: &mut FastLogRecord) {
+        match &arg.command {
+            Command::CommandRecord => {
+                //write into the buffer,it may be taken from BufferPool
+                let mut out = std::mem::take(&mut arg.formated);
+                out.clear();
+                out.push_str("ts=");
+                self.write_ts(&mut out, arg);
+                out.push_str(" level=");
+                let start = out.len();
+                for c in arg.level.as_str().chars() {
+                    out.push(c.to_ascii_lowercase());
+                }
+                arg.level_span = Some(start..out.len());
+                out.push_str(" msg=");
+                write_logfmt_value(&mut out, &arg.args);
+                if self.target {
+                    out.push_str(" target=");
+                    write_logfmt_value(&mut out, &arg.target);
+                }
+                if self.module_path {
+                    out.push_str(" module=");
+                    write_logfmt_value(&mut out, &arg.module_path);
+                }
+                if self.file {
+                    out.push_str(" file=");
+                    write_logfmt_value(&mut out, &arg.file);
+                    let _ = write!(out, " line={}", arg.line.unwrap_or_default());
+                }
+                if self.thread {
+                    if let Some(name) = arg.thread_name() {
+                        out.push_str(" thread=");
+                        write_logfmt_value(&mut out, &name);
+                    }
+                }
+                for (k, v) in &arg.fields {
+                    out.push(' ');
+                    write_logfmt_key(&mut out, k);
+                    out.push('=');
+                    match v {
+                        FieldValue::Str(v) => write_logfmt_value(&mut out, v),
+                        _ => {
+                            let _ = write!(out, "{}", v);
+                        }
+                    }
+                }
+                out.push('\n');
+                arg.formated = out;
+            }
+            Command::CommandExit => {}
+            Command::CommandFlush(_) => {}
+            Command::CommandReconfigure(_) => {}
+        }
+    }
+}
+
+impl LogfmtFormat {
+    pub fn new() -> LogfmtFormat {
+        Self::default()
+    }
+
+    /// set time_type
+    pub fn set_time_type(mut self, time_type: TimeType) -> Self {
+        self.time_type = time_type;
+        self
+    }
+
+    /// set the style of `ts`
+    pub fn set_timestamp(mut self, timestamp: TimestampStyle) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    /// write `target=`
+    pub fn set_target(mut self, target: bool) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// write `module=`,default true
+    pub fn set_module_path(mut self, module_path: bool) -> Self {
+        self.module_path = module_path;
+        self
+    }
+
+    /// write `file=` and `line=`
+    pub fn set_file(mut self, file: bool) -> Self {
+        self.file = file;
+        self
+    }
+
+    /// write `thread=`
+    pub fn set_thread(mut self, thread: bool) -> Self {
+        self.thread = thread;
+        self
+    }
+
+    fn write_ts(&self, out: &mut String, arg: &FastLogRecord) {
+        let now = match self.time_type {
+            TimeType::Local => fastdate::DateTime::from(arg.now).set_offset(fastdate::offset_sec()),
+            TimeType::Utc => fastdate::DateTime::from(arg.now),
+        };
+        let _ = match self.timestamp {
+            TimestampStyle::Rfc3339 => {
+                let _ = write!(
+                    out,
+                    "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}",
+                    now.year(),
+                    now.mon(),
+                    now.day(),
+                    now.hour(),
+                    now.minute(),
+                    now.sec(),
+                    now.nano() / 1_000_000
+                );
+                match self.time_type {
+                    TimeType::Utc => write!(out, "Z"),
+                    TimeType::Local => {
+                        let offset = now.offset();
+                        let sign = if offset < 0 { '-' } else { '+' };
+                        let offset = offset.unsigned_abs() / 60;
+                        write!(out, "{}{:02}:{:02}", sign, offset / 60, offset % 60)
+                    }
+                }
+            }
+            TimestampStyle::Stand => write!(out, "\"{}\"", display_ms(&now)),
+            TimestampStyle::UnixSecs => write!(out, "{}", now.unix_timestamp()),
+            TimestampStyle::UnixMillis => write!(out, "{}", now.unix_timestamp_millis()),
+        };
+    }
+}
+
+/// write a logfmt value,it is quoted and escaped like a json string when needed
+pub fn write_logfmt_value(out: &mut String, v: &str) {
+    if v.is_empty() || v.contains(|c: char| c <= ' ' || c == '=' || c == '"' || c.is_control()) {
+        write_json_str(out, v, false);
+    } else {
+        out.push_str(v);
+    }
+}
+
+/// write a logfmt key,the chars which can not be in a key are skipped
+fn write_logfmt_key(out: &mut String, k: &str) {
+    let start = out.len();
+    for c in k.chars() {
+        if !(c <= ' ' || c == '=' || c == '"' || c.is_control()) {
+            out.push(c);
+        }
+    }
+    if out.len() == start {
+        out.push('_');
+    }
 }
@@ -8,7 +8,9 @@ pub mod fastlog;
 pub mod filter;
 pub mod formats;
 pub mod plugin;
+pub mod pool;
 pub mod runtime;
+pub mod stats;
 
 pub use config::*;
 pub use formats::*;
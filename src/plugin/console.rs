@@ -1,24 +1,193 @@
-use crate::appender::{FastLogRecord, LogAppender};
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use crate::error::LogError;
+use log::{Level, LevelFilter};
+use std::ffi::OsString;
+use std::io::{ErrorKind, IsTerminal, StderrLock, StdoutLock, Write};
 
-/// only write append into console
-pub struct ConsoleAppender {}
+/// whether the console writes ANSI colors
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// colors when the console is a terminal,`NO_COLOR` disables and `CLICOLOR_FORCE` forces them
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// resolve the choice for a console
+    pub fn enabled(&self, is_terminal: bool) -> bool {
+        self.enabled_by(is_terminal, |key| std::env::var_os(key))
+    }
+
+    /// resolve the choice for a console with the environment variables read by `var`
+    pub fn enabled_by<F>(&self, is_terminal: bool, var: F) -> bool
+    where
+        F: Fn(&str) -> Option<OsString>,
+    {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => {
+                let env = |key: &str| var(key).filter(|v| !v.is_empty());
+                if env("NO_COLOR").is_some() {
+                    false
+                } else if env("CLICOLOR_FORCE").is_some_and(|v| v != "0") {
+                    true
+                } else {
+                    is_terminal && env("TERM").is_none_or(|v| v != "dumb")
+                }
+            }
+        }
+    }
+}
+
+/// only write append into console.
+/// the records at or above the stderr level go to stderr,a broken pipe closes the stream instead of panicking.
+/// create it by `ConsoleAppender::new()`
+#[non_exhaustive]
+pub struct ConsoleAppender {
+    color: ColorChoice,
+    stdout_colored: bool,
+    stderr_colored: bool,
+    stderr_level: LevelFilter,
+    stdout_closed: bool,
+    stderr_closed: bool,
+    buffer: String,
+    error: Option<LogError>,
+}
+
+impl Default for ConsoleAppender {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConsoleAppender {
+    /// write all records to stdout,colors are enabled when the stream is a terminal
+    pub fn new() -> Self {
+        Self {
+            color: ColorChoice::Auto,
+            stdout_colored: ColorChoice::Auto.enabled(std::io::stdout().is_terminal()),
+            stderr_colored: ColorChoice::Auto.enabled(std::io::stderr().is_terminal()),
+            stderr_level: LevelFilter::Off,
+            stdout_closed: false,
+            stderr_closed: false,
+            buffer: String::new(),
+            error: None,
+        }
+    }
+
+    /// set the color choice
+    pub fn color(mut self, color: ColorChoice) -> Self {
+        self.color = color;
+        self.stdout_colored = color.enabled(std::io::stdout().is_terminal());
+        self.stderr_colored = color.enabled(std::io::stderr().is_terminal());
+        self
+    }
+
+    /// the color choice
+    pub fn color_choice(&self) -> ColorChoice {
+        self.color
+    }
+
+    /// write the records at or above the level to stderr,for example `LevelFilter::Warn` for WARN and ERROR
+    pub fn stderr(mut self, level: LevelFilter) -> Self {
+        self.stderr_level = level;
+        self
+    }
+
+    /// write the buffer to stdout or stderr,a broken pipe closes the stream
+    fn write_buffer(
+        &mut self,
+        stdout: &mut StdoutLock,
+        stderr: &mut Option<StderrLock>,
+        to_stderr: bool,
+    ) {
+        let (stream, closed, name): (Option<&mut dyn Write>, _, _) = match to_stderr {
+            true => (
+                stderr.as_mut().map(|v| v as &mut dyn Write),
+                &mut self.stderr_closed,
+                "stderr",
+            ),
+            false => (Some(stdout), &mut self.stdout_closed, "stdout"),
+        };
+        if let (Some(stream), false) = (stream, *closed) {
+            if let Err(e) = stream
+                .write_all(self.buffer.as_bytes())
+                .and_then(|_| stream.flush())
+            {
+                if e.kind() == ErrorKind::BrokenPipe {
+                    *closed = true;
+                }
+                self.error = Some(LogError::from(format!("console {}: {}", name, e)));
+            }
+        }
+        self.buffer.clear();
+    }
+}
 
 impl LogAppender for ConsoleAppender {
     fn do_logs(&mut self, records: &[FastLogRecord]) {
-        if records.len() == 0 {
+        if records.is_empty() {
             return;
         }
-        let mut cap = 0;
-        if records.len() != 0 {
-            cap = 0;
-            for x in records {
-                cap += x.formated.len();
+        //lock once per batch
+        let mut stdout = std::io::stdout().lock();
+        let mut stderr = (self.stderr_level != LevelFilter::Off).then(|| std::io::stderr().lock());
+        let mut to_stderr = false;
+        for x in records {
+            let is_record = x.command == Command::CommandRecord;
+            let next = is_record && x.level <= self.stderr_level;
+            //keep the order of records,the buffer is written when the stream changes
+            if next != to_stderr && !self.buffer.is_empty() {
+                self.write_buffer(&mut stdout, &mut stderr, to_stderr);
+            }
+            to_stderr = next;
+            let colored = match to_stderr {
+                true => self.stderr_colored,
+                false => self.stdout_colored,
+            };
+            if colored && is_record {
+                paint_level(&mut self.buffer, x);
+            } else {
+                self.buffer.push_str(&x.formated);
             }
         }
-        let mut buffer = String::with_capacity(cap);
-        for x in records {
-            buffer.push_str(&x.formated);
+        if !self.buffer.is_empty() {
+            self.write_buffer(&mut stdout, &mut stderr, to_stderr);
+        }
+    }
+
+    fn take_error(&mut self) -> Option<LogError> {
+        self.error.take()
+    }
+}
+
+/// the ANSI color of level
+pub fn level_color(level: Level) -> &'static str {
+    match level {
+        Level::Error => "\x1b[31m",
+        Level::Warn => "\x1b[33m",
+        Level::Info => "\x1b[32m",
+        Level::Debug => "\x1b[34m",
+        Level::Trace => "\x1b[35m",
+    }
+}
+
+/// write the formatted record with the level colored.
+/// the level name is at `FastLogRecord::level_span` set by the format,a record without it is written as it is,for example `fastlog::print`
+pub fn paint_level(out: &mut String, record: &FastLogRecord) {
+    let line = record.formated.as_str();
+    let span = record.level_span.clone();
+    match span.filter(|v| line.get(v.clone()).is_some()) {
+        Some(span) => {
+            out.push_str(&line[..span.start]);
+            out.push_str(level_color(record.level));
+            out.push_str(&line[span.clone()]);
+            out.push_str("\x1b[0m");
+            out.push_str(&line[span.end..]);
         }
-        print!("{}", buffer);
+        None => out.push_str(line),
     }
 }
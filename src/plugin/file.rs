@@ -7,6 +7,7 @@ use std::io::Write;
 /// only write append into file
 pub struct FileAppender {
     file: RefCell<File>,
+    error: Option<LogError>,
 }
 
 impl FileAppender {
@@ -23,6 +24,7 @@ impl FileAppender {
                     .append(true)
                     .open(&log_file_path)?,
             ),
+            error: None,
         })
     }
 }
@@ -31,7 +33,7 @@ impl LogAppender for FileAppender {
     fn do_logs(&mut self, records: &[FastLogRecord]) {
         let mut log_file = self.file.borrow_mut();
         let mut cap = 0;
-        if records.len() != 0 {
+        if !records.is_empty() {
             cap = 0;
             for x in records {
                 cap += x.formated.len();
@@ -43,13 +45,24 @@ impl LogAppender for FileAppender {
             match &x.command {
                 Command::CommandRecord => {}
                 Command::CommandExit => {}
+                Command::CommandReconfigure(_) => {}
                 Command::CommandFlush(_) => {
-                    let _ = log_file.write_all(buf.as_bytes());
-                    let _ = log_file.flush();
+                    if let Err(e) = log_file
+                        .write_all(buf.as_bytes())
+                        .and_then(|_| log_file.flush())
+                    {
+                        self.error = Some(e.into());
+                    }
                     buf.clear();
                 }
             }
         }
-        let _ = log_file.write_all(buf.as_bytes());
+        if let Err(e) = log_file.write_all(buf.as_bytes()) {
+            self.error = Some(e.into());
+        }
+    }
+
+    fn take_error(&mut self) -> Option<LogError> {
+        self.error.take()
     }
 }
@@ -0,0 +1,304 @@
+use crate::appender::{Command, FastLogRecord, FieldValue, LogAppender};
+use crate::error::LogError;
+use crate::plugin::file::FileAppender;
+use crate::pool::buffer_pool;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+/// create the appender of a path
+pub type FileFactory = Box<dyn Fn(&str) -> Result<Box<dyn LogAppender>, LogError> + Send>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum PathPiece {
+    Literal(String),
+    Target,
+    ModulePath,
+    Level,
+    Field(String),
+}
+
+struct OpenFile {
+    appender: Box<dyn LogAppender>,
+    last_used: Instant,
+    batch: Vec<FastLogRecord>,
+}
+
+impl OpenFile {
+    /// write the records dispatched to the file
+    fn write_batch(&mut self) {
+        if !self.batch.is_empty() {
+            self.appender.do_logs(&self.batch);
+        }
+        for x in self.batch.drain(..) {
+            buffer_pool().recycle(x);
+        }
+    }
+}
+
+/// a file per key,the path is a template like `logs/{target}.log` or `logs/tenant-{kv.tenant}/app.log`.
+/// placeholders are `{target}`,`{module_path}`,`{level}` and `{kv.key}`,a value is made safe for a path and a missing key-value is `unknown`.
+/// the files are opened lazily,the least recently used file is closed when `max_open` files are open,and an idle file is closed after `idle_timeout`
+/// ```rust
+/// use fastlog::plugin::file_dynamic::DynamicFileAppender;
+/// use fastlog::{AppenderConfig, Config};
+/// let tenants = DynamicFileAppender::new("target/logs/tenant-{kv.tenant}/app.log")
+///     .unwrap()
+///     .max_open(256);
+/// fastlog::init(Config::new().append(AppenderConfig::new(tenants))).unwrap();
+/// log::info!(tenant = "a"; "login");
+/// ```
+pub struct DynamicFileAppender {
+    pieces: Vec<PathPiece>,
+    factory: FileFactory,
+    max_open: usize,
+    idle_timeout: Option<Duration>,
+    files: HashMap<String, OpenFile>,
+    last_idle_check: Instant,
+    error: Option<LogError>,
+}
+
+impl DynamicFileAppender {
+    /// a `FileAppender` per key
+    pub fn new(template: &str) -> Result<Self, LogError> {
+        Self::with_factory(template, FileAppender::new)
+    }
+
+    /// an appender per key by the factory,for example a `FileSplitAppender` with the same rolling/keep/packer settings:
+    /// ```rust
+    /// use fastlog::consts::LogSize;
+    /// use fastlog::plugin::file_dynamic::DynamicFileAppender;
+    /// use fastlog::plugin::file_split::{FileSplitAppender, KeepType, RawFile, Rolling, RollingType};
+    /// use fastlog::plugin::packer::LogPacker;
+    /// let tenants = DynamicFileAppender::with_factory("target/logs/{kv.tenant}/", |path| {
+    ///     FileSplitAppender::new::<RawFile>(
+    ///         path,
+    ///         Box::new(Rolling::new(RollingType::BySize(LogSize::MB(100)))),
+    ///         Box::new(KeepType::KeepNum(5)),
+    ///         Box::new(LogPacker {}),
+    ///     )
+    /// })
+    /// .unwrap();
+    /// ```
+    pub fn with_factory<A, F>(template: &str, factory: F) -> Result<Self, LogError>
+    where
+        A: LogAppender + 'static,
+        F: Fn(&str) -> Result<A, LogError> + Send + 'static,
+    {
+        Ok(Self {
+            pieces: compile_path(template)?,
+            factory: Box::new(move |path| Ok(Box::new(factory(path)?))),
+            max_open: 128,
+            idle_timeout: None,
+            files: HashMap::new(),
+            last_idle_check: Instant::now(),
+            error: None,
+        })
+    }
+
+    /// set the max open files,default is 128
+    pub fn max_open(mut self, max_open: usize) -> Self {
+        self.max_open = max_open.max(1);
+        self
+    }
+
+    /// close a file that is not written for the duration
+    pub fn idle_timeout(mut self, idle_timeout: Duration) -> Self {
+        self.idle_timeout = Some(idle_timeout);
+        self
+    }
+
+    /// the count of open files
+    pub fn open_files(&self) -> usize {
+        self.files.len()
+    }
+
+    fn render(&self, record: &FastLogRecord) -> String {
+        let mut path = String::new();
+        for piece in &self.pieces {
+            match piece {
+                PathPiece::Literal(v) => path.push_str(v),
+                PathPiece::Target => push_safe(&mut path, &record.target),
+                PathPiece::ModulePath => push_safe(&mut path, &record.module_path),
+                PathPiece::Level => path.push_str(record.level.as_str()),
+                PathPiece::Field(key) => match record.field(key) {
+                    Some(FieldValue::Str(v)) => push_safe(&mut path, v),
+                    Some(v) => {
+                        let _ = write!(path, "{}", v);
+                    }
+                    None => path.push_str("unknown"),
+                },
+            }
+        }
+        path
+    }
+
+    /// open the file of path,the least recently used file is closed when it is full
+    fn open(&mut self, path: &str) -> bool {
+        if self.files.contains_key(path) {
+            return true;
+        }
+        if self.files.len() >= self.max_open {
+            if let Some(lru) = self
+                .files
+                .iter()
+                .min_by_key(|(_, v)| v.last_used)
+                .map(|(k, _)| k.clone())
+            {
+                self.close(&lru);
+            }
+        }
+        match (self.factory)(path) {
+            Ok(appender) => {
+                self.files.insert(
+                    path.to_string(),
+                    OpenFile {
+                        appender,
+                        last_used: Instant::now(),
+                        batch: vec![],
+                    },
+                );
+                true
+            }
+            Err(e) => {
+                self.error = Some(LogError::from(format!("open {}: {}", path, e)));
+                false
+            }
+        }
+    }
+
+    /// close the file,the records dispatched to it in this batch are written first
+    fn close(&mut self, path: &str) {
+        if let Some(mut file) = self.files.remove(path) {
+            file.write_batch();
+            if let Some(e) = file.appender.take_error() {
+                self.error = Some(e);
+            }
+        }
+    }
+
+    /// close the idle files,it is checked at most once a second
+    fn close_idle(&mut self) {
+        let Some(idle_timeout) = self.idle_timeout else {
+            return;
+        };
+        if self.last_idle_check.elapsed() < idle_timeout.min(Duration::from_secs(1)) {
+            return;
+        }
+        self.last_idle_check = Instant::now();
+        let idle: Vec<String> = self
+            .files
+            .iter()
+            .filter(|(_, v)| v.last_used.elapsed() >= idle_timeout)
+            .map(|(k, _)| k.clone())
+            .collect();
+        for path in idle {
+            self.close(&path);
+        }
+    }
+}
+
+impl LogAppender for DynamicFileAppender {
+    fn do_logs(&mut self, records: &[FastLogRecord]) {
+        self.close_idle();
+        let now = Instant::now();
+        //a record is dispatched once its file is open,an evicted file writes its records when closed
+        for x in records {
+            match x.command {
+                Command::CommandRecord => {
+                    let path = self.render(x);
+                    if !self.open(&path) {
+                        continue;
+                    }
+                    if let Some(file) = self.files.get_mut(&path) {
+                        file.last_used = now;
+                        file.batch.push(x.clone());
+                    }
+                }
+                //flush and exit go to all open files
+                _ => {
+                    for file in self.files.values_mut() {
+                        file.batch.push(x.clone());
+                    }
+                }
+            }
+        }
+        for file in self.files.values_mut() {
+            file.write_batch();
+        }
+    }
+
+    fn take_error(&mut self) -> Option<LogError> {
+        for file in self.files.values_mut() {
+            if let Some(e) = file.appender.take_error() {
+                self.error = Some(e);
+            }
+        }
+        self.error.take()
+    }
+
+    /// the idle files are closed while no record is written
+    fn next_tick(&self) -> Option<Duration> {
+        match self.idle_timeout {
+            Some(idle_timeout) if !self.files.is_empty() => {
+                Some(idle_timeout.min(Duration::from_secs(1)))
+            }
+            _ => None,
+        }
+    }
+
+    fn tick(&mut self) {
+        self.close_idle();
+    }
+}
+
+fn compile_path(template: &str) -> Result<Vec<PathPiece>, LogError> {
+    let mut pieces = vec![];
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        if start > 0 {
+            pieces.push(PathPiece::Literal(rest[..start].to_string()));
+        }
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| LogError::from(format!("path `{}` has an unclosed `{{`", template)))?
+            + start;
+        pieces.push(match &rest[start + 1..end] {
+            "target" => PathPiece::Target,
+            "module_path" => PathPiece::ModulePath,
+            "level" => PathPiece::Level,
+            v => match v.strip_prefix("kv.") {
+                Some(key) if !key.is_empty() => PathPiece::Field(key.to_string()),
+                _ => {
+                    return Err(LogError::from(format!(
+                        "path `{}` has an unknown placeholder `{{{}}}`",
+                        template, v
+                    )))
+                }
+            },
+        });
+        rest = &rest[end + 1..];
+    }
+    if !rest.is_empty() {
+        pieces.push(PathPiece::Literal(rest.to_string()));
+    }
+    Ok(pieces)
+}
+
+/// push a value which can not leave the directory,the chars except `[A-Za-z0-9_.-]` are `_` and `..` is `__`
+fn push_safe(path: &mut String, v: &str) {
+    if v.is_empty() {
+        path.push_str("unknown");
+        return;
+    }
+    let start = path.len();
+    for c in v.chars() {
+        match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' | '.' => path.push(c),
+            _ => path.push('_'),
+        }
+    }
+    if path[start..].chars().all(|c| c == '.') {
+        path.replace_range(start.., &"_".repeat(path.len() - start));
+    }
+}
@@ -26,4 +26,8 @@ impl LogAppender for FileLoopAppender {
     fn do_logs(&mut self, records: &[FastLogRecord]) {
         self.file.do_logs(records);
     }
+
+    fn take_error(&mut self) -> Option<LogError> {
+        self.file.take_error()
+    }
 }
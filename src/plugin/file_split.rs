@@ -2,7 +2,7 @@ use crate::appender::{Command, FastLogRecord, LogAppender};
 use crate::consts::LogSize;
 use crate::error::LogError;
 use crate::plugin::file_name::FileName;
-use crate::{chan, Receiver, Sender, WaitGroup};
+use crate::{chan, JoinHandle, Receiver, Sender, WaitGroup};
 use fastdate::DateTime;
 use std::cell::RefCell;
 use std::fs::{DirEntry, File, OpenOptions};
@@ -21,7 +21,7 @@ pub trait Packer: Send + Sync {
 
     /// default 0 is not retry pack. if retry > 0 ,it will trying rePack
     fn retry(&self) -> i32 {
-        return 0;
+        0
     }
 }
 
@@ -60,29 +60,25 @@ pub trait Keep: Send {
         if let Ok(paths) = paths {
             //let mut temp_file = None;
             let mut paths_vec = vec![];
-            for path in paths {
-                match path {
-                    Ok(path) => {
-                        if let Some(v) = path.file_name().to_str() {
-                            if v == temp_name {
-                                continue;
-                            }
-                            if !v.starts_with(&base_name) {
-                                continue;
-                            }
-                        }
-                        paths_vec.push(path);
+            for path in paths.flatten() {
+                if let Some(v) = path.file_name().to_str() {
+                    if v == temp_name {
+                        continue;
+                    }
+                    if !v.starts_with(&base_name) {
+                        continue;
                     }
-                    _ => {}
                 }
+                paths_vec.push(path);
             }
-            paths_vec.sort_by(|a, b| b.file_name().cmp(&a.file_name()));
+            paths_vec.sort_by_key(|b| std::cmp::Reverse(b.file_name()));
             return paths_vec;
         }
-        return vec![];
+        vec![]
     }
 }
 
+#[allow(clippy::len_without_is_empty)]
 pub trait SplitFile: Send {
     fn new(path: &str) -> Result<Self, LogError>
     where
@@ -115,9 +111,10 @@ impl SplitFile for RawFile {
     {
         let file = OpenOptions::new()
             .create(true)
+            .truncate(false)
             .read(true)
             .write(true)
-            .open(&path)?;
+            .open(path)?;
         Ok(Self {
             inner: RefCell::new(file),
         })
@@ -151,29 +148,21 @@ impl SplitFile for RawFile {
     }
 
     fn offset(&self) -> usize {
-        let mut offset = self.len();
-        if offset > 0 {
-            offset = offset - 1;
-        }
-        offset
+        self.len().saturating_sub(1)
     }
 }
 
+#[derive(Default)]
 pub enum DateType {
     Sec,
     Hour,
     Minute,
+    #[default]
     Day,
     Month,
     Year,
 }
 
-impl Default for DateType {
-    fn default() -> Self {
-        Self::Day
-    }
-}
-
 #[allow(dead_code)]
 pub struct DurationType {
     last: DateTime,
@@ -201,7 +190,7 @@ impl Rolling {
     pub fn new(how: RollingType) -> Self {
         Self {
             last: SystemTime::now(),
-            how: how,
+            how,
         }
     }
 }
@@ -221,9 +210,9 @@ impl CanRollingPack for Rolling {
         temp_size: usize,
         arg: &FastLogRecord,
     ) -> Option<String> {
-        let last_time = self.last.clone();
-        self.last = arg.now.clone();
-        return match &mut self.how {
+        let last_time = self.last;
+        self.last = arg.now;
+        match &mut self.how {
             RollingType::ByDate(date_type) => {
                 let last_time = DateTime::from_system_time(last_time, fastdate::offset_sec());
                 let log_time = DateTime::from_system_time(arg.now, fastdate::offset_sec());
@@ -278,7 +267,7 @@ impl CanRollingPack for Rolling {
             }
             RollingType::ByDuration((start_time, duration)) => {
                 let log_time = DateTime::from_system_time(arg.now, fastdate::offset_sec());
-                let next = start_time.clone().add(duration.clone());
+                let next = start_time.clone().add(*duration);
                 if log_time >= next {
                     let now = DateTime::now();
                     let last_time = DateTime::from_system_time(last_time, fastdate::offset_sec());
@@ -301,7 +290,7 @@ impl CanRollingPack for Rolling {
                     None
                 }
             }
-        };
+        }
     }
 }
 
@@ -316,6 +305,8 @@ pub struct FileSplitAppender {
     //cache data
     temp_bytes: AtomicUsize,
     temp_name: String,
+    saver: Option<JoinHandle<()>>,
+    error: Option<LogError>,
 }
 
 impl FileSplitAppender {
@@ -354,7 +345,7 @@ impl FileSplitAppender {
         let _ = file.seek(SeekFrom::Start(temp_bytes.load(Ordering::Relaxed) as u64));
         let (sender, receiver) = chan(None);
         let arc_packer = Arc::new(packer);
-        spawn_saver(temp_name.clone(), receiver, keeper, arc_packer.clone());
+        let saver = spawn_saver(temp_name.clone(), receiver, keeper, arc_packer.clone());
         Ok(Self {
             temp_bytes,
             dir_path: dir_path.to_string(),
@@ -363,6 +354,8 @@ impl FileSplitAppender {
             can_pack: rolling,
             temp_name,
             packer: arc_packer,
+            saver: Some(saver),
+            error: None,
         })
     }
     /// send data make an pack,and truncate data when finish.
@@ -392,6 +385,18 @@ impl FileSplitAppender {
     pub fn temp_name(&self) -> &str {
         &self.temp_name
     }
+
+    /// write into the temp file,the error is kept for `LogAppender::take_error`
+    fn write_temp(&mut self, temp: &str) {
+        match self.file.write(temp.as_bytes()) {
+            Ok(w) => {
+                self.temp_bytes.fetch_add(w, Ordering::SeqCst);
+            }
+            Err(e) => {
+                self.error = Some(e.into());
+            }
+        }
+    }
 }
 
 ///log data pack
@@ -403,7 +408,7 @@ pub struct LogPack {
 
 impl LogPack {
     /// write an Pack to zip file
-    pub fn do_pack(&self, packer: &Box<dyn Packer>) -> Result<bool, LogError> {
+    pub fn do_pack(&self, packer: &dyn Packer) -> Result<bool, LogError> {
         let log_file_path = self.new_log_name.as_str();
         if log_file_path.is_empty() {
             return Err(LogError::from("log_file_path.is_empty"));
@@ -429,7 +434,7 @@ impl LogPack {
         if let Ok(b) = r {
             return Ok(b);
         }
-        return Ok(false);
+        Ok(false)
     }
 }
 
@@ -456,23 +461,19 @@ impl Keep for KeepType {
             }
             KeepType::KeepNum(n) => {
                 let paths_vec = self.read_paths(dir, temp_name);
-                for index in 0..paths_vec.len() {
-                    if index >= (*n) as usize {
-                        let item = &paths_vec[index];
-                        let _ = std::fs::remove_file(item.path());
-                        removed += 1;
-                    }
+                for item in paths_vec.iter().skip(*n as usize) {
+                    let _ = std::fs::remove_file(item.path());
+                    removed += 1;
                 }
             }
             KeepType::KeepTime(duration) => {
                 let paths_vec = self.read_paths(dir, temp_name);
                 let now = DateTime::now();
-                for index in 0..paths_vec.len() {
-                    let item = &paths_vec[index];
+                for item in &paths_vec {
                     if let Ok(m) = item.metadata() {
                         if let Ok(c) = m.created() {
                             let time = DateTime::from(c);
-                            if now.clone().sub(duration.clone()) > time {
+                            if now.clone().sub(*duration) > time {
                                 let _ = std::fs::remove_file(item.path());
                                 removed += 1;
                             }
@@ -489,7 +490,7 @@ impl LogAppender for FileSplitAppender {
     fn do_logs(&mut self, records: &[FastLogRecord]) {
         //if temp_bytes is full,must send pack
         let mut cap = 0;
-        if records.len() != 0 {
+        if !records.is_empty() {
             cap = 0;
             for x in records {
                 cap += x.formated.len();
@@ -499,32 +500,22 @@ impl LogAppender for FileSplitAppender {
         for x in records {
             match x.command {
                 Command::CommandRecord => {
-                    let current_temp_size = self.temp_bytes.load(Ordering::Relaxed)
-                        + temp.as_bytes().len()
-                        + x.formated.as_bytes().len();
+                    let current_temp_size =
+                        self.temp_bytes.load(Ordering::Relaxed) + temp.len() + x.formated.len();
                     if let Some(new_log_name) = self.can_pack.can(
                         self.packer.deref(),
                         &self.temp_name,
                         current_temp_size,
                         x,
                     ) {
-                        self.temp_bytes.fetch_add(
-                            {
-                                let w = self.file.write(temp.as_bytes());
-                                if let Ok(w) = w {
-                                    w
-                                } else {
-                                    0
-                                }
-                            },
-                            Ordering::SeqCst,
-                        );
+                        self.write_temp(&temp);
                         temp.clear();
                         self.send_pack(new_log_name, None);
                     }
                     temp.push_str(x.formated.as_str());
                 }
                 Command::CommandExit => {}
+                Command::CommandReconfigure(_) => {}
                 Command::CommandFlush(ref w) => {
                     let current_temp_size = self.temp_bytes.load(Ordering::Relaxed);
                     if let Some(new_log_name) = self.can_pack.can(
@@ -533,17 +524,7 @@ impl LogAppender for FileSplitAppender {
                         current_temp_size,
                         x,
                     ) {
-                        self.temp_bytes.fetch_add(
-                            {
-                                let w = self.file.write(temp.as_bytes());
-                                if let Ok(w) = w {
-                                    w
-                                } else {
-                                    0
-                                }
-                            },
-                            Ordering::SeqCst,
-                        );
+                        self.write_temp(&temp);
                         temp.clear();
                         self.send_pack(new_log_name, Some(w.clone()));
                     }
@@ -551,49 +532,48 @@ impl LogAppender for FileSplitAppender {
             }
         }
         if !temp.is_empty() {
-            let _ = self.temp_bytes.fetch_add(
-                {
-                    let w = self.file.write(temp.as_bytes());
-                    if let Ok(w) = w {
-                        w
-                    } else {
-                        0
-                    }
-                },
-                Ordering::SeqCst,
-            );
+            self.write_temp(&temp);
+        }
+    }
+
+    fn take_error(&mut self) -> Option<LogError> {
+        self.error.take()
+    }
+}
+
+impl Drop for FileSplitAppender {
+    fn drop(&mut self) {
+        //close the channel,and wait the saver thread finish packs
+        self.sender = chan(None).0;
+        if let Some(saver) = self.saver.take() {
+            let _ = saver.join();
         }
     }
 }
 
-///spawn an saver thread to save log file or zip file
+///spawn an saver thread to save log file or zip file.
+///the thread exit when the sender is dropped
 fn spawn_saver(
     temp_name: String,
     r: Receiver<LogPack>,
     rolling_type: Box<dyn Keep>,
     packer: Arc<Box<dyn Packer>>,
-) {
+) -> JoinHandle<()> {
     std::thread::spawn(move || {
-        loop {
-            if let Ok(pack) = r.recv() {
-                if pack.wg.is_some() {
-                    return;
-                }
-                let log_file_path = pack.new_log_name.clone();
-                //do save pack
-                let remove = pack.do_pack(packer.as_ref());
-                if let Ok(remove) = remove {
-                    if remove {
-                        let _ = std::fs::remove_file(log_file_path);
-                    }
+        //the pack.wg of flush is dropped after pack done
+        while let Ok(pack) = r.recv() {
+            let log_file_path = pack.new_log_name.clone();
+            //do save pack
+            let remove = pack.do_pack(packer.as_ref());
+            if let Ok(remove) = remove {
+                if remove {
+                    let _ = std::fs::remove_file(log_file_path);
                 }
-                //do rolling
-                rolling_type.do_keep(&pack.dir, &temp_name);
-            } else {
-                break;
             }
+            //do rolling
+            rolling_type.do_keep(&pack.dir, &temp_name);
         }
-    });
+    })
 }
 
 fn get_base_name(path: &str) -> String {
@@ -0,0 +1,185 @@
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use crate::error::LogError;
+use log::Level;
+use parking_lot::Mutex;
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+struct Recorder {
+    records: VecDeque<FastLogRecord>,
+    bytes: usize,
+    max_records: usize,
+    max_bytes: Option<usize>,
+    max_age: Option<Duration>,
+    appender: Box<dyn LogAppender>,
+}
+
+impl Recorder {
+    fn push(&mut self, record: &FastLogRecord) {
+        self.bytes += record.formated.len();
+        self.records.push_back(record.clone());
+        while self.records.len() > self.max_records
+            || self.max_bytes.is_some_and(|v| self.bytes > v)
+        {
+            self.pop();
+        }
+    }
+
+    fn pop(&mut self) {
+        if let Some(v) = self.records.pop_front() {
+            self.bytes -= v.formated.len();
+        }
+    }
+
+    /// drop the records older than max_age
+    fn expire(&mut self, now: SystemTime) {
+        if let Some(max_age) = self.max_age {
+            while self
+                .records
+                .front()
+                .is_some_and(|v| now.duration_since(v.now).unwrap_or_default() > max_age)
+            {
+                self.pop();
+            }
+        }
+    }
+
+    /// write the kept records and the trigger record to the wrapped appender
+    fn dump(&mut self, trigger: Option<&FastLogRecord>) -> usize {
+        self.expire(trigger.map(|v| v.now).unwrap_or_else(SystemTime::now));
+        if let Some(trigger) = trigger {
+            self.records.push_back(trigger.clone());
+        }
+        let len = self.records.len();
+        if len > 0 {
+            self.appender.do_logs(self.records.make_contiguous());
+        }
+        self.records.clear();
+        self.bytes = 0;
+        len
+    }
+}
+
+/// a handle of `FlightRecorderAppender`,it can dump the kept records on demand
+#[derive(Clone)]
+pub struct FlightRecorder {
+    inner: Arc<Mutex<Recorder>>,
+}
+
+impl FlightRecorder {
+    /// write the kept records to the wrapped appender,return the count of records.
+    /// the records still in the queue of logger are not kept yet,call `fastlog::flush()` before it for them
+    pub fn dump(&self) -> usize {
+        self.inner.lock().dump(None)
+    }
+
+    /// the count of kept records
+    pub fn len(&self) -> usize {
+        self.inner.lock().records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// keep the last records of all levels in memory,and write them to the wrapped appender when a record at or above the trigger level arrives.
+/// the logger level must allow the kept levels,for example `Config::new().level(LevelFilter::Debug)`,and other appenders can use `AppenderConfig::level`
+/// ```rust
+/// use fastlog::plugin::file::FileAppender;
+/// use fastlog::plugin::flight_recorder::FlightRecorderAppender;
+/// use fastlog::{AppenderConfig, Config};
+/// use log::{Level, LevelFilter};
+/// let recorder = FlightRecorderAppender::new(FileAppender::new("target/logs/crash.log").unwrap())
+///     .max_records(10000)
+///     .trigger(Level::Error);
+/// let handle = recorder.recorder();
+/// fastlog::init(
+///     Config::new()
+///         .level(LevelFilter::Debug)
+///         .append(AppenderConfig::new(recorder)),
+/// )
+/// .unwrap();
+/// log::debug!("context");
+/// log::error!("boom"); // crash.log: context,boom
+/// handle.dump();
+/// ```
+pub struct FlightRecorderAppender {
+    inner: Arc<Mutex<Recorder>>,
+    trigger: Level,
+}
+
+impl FlightRecorderAppender {
+    /// keep the last 1000 records,an ERROR record dumps them
+    pub fn new<A: LogAppender + 'static>(appender: A) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Recorder {
+                records: VecDeque::new(),
+                bytes: 0,
+                max_records: 1000,
+                max_bytes: None,
+                max_age: None,
+                appender: Box::new(appender),
+            })),
+            trigger: Level::Error,
+        }
+    }
+
+    /// keep at most the last records
+    pub fn max_records(self, max_records: usize) -> Self {
+        self.inner.lock().max_records = max_records;
+        self
+    }
+
+    /// keep at most the bytes of formatted records
+    pub fn max_bytes(self, max_bytes: usize) -> Self {
+        self.inner.lock().max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// keep the records of the last duration
+    pub fn max_age(self, max_age: Duration) -> Self {
+        self.inner.lock().max_age = Some(max_age);
+        self
+    }
+
+    /// a record at or above the level dumps the kept records,default is `Level::Error`
+    pub fn trigger(mut self, level: Level) -> Self {
+        self.trigger = level;
+        self
+    }
+
+    /// a handle for `FlightRecorder::dump`
+    pub fn recorder(&self) -> FlightRecorder {
+        FlightRecorder {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl LogAppender for FlightRecorderAppender {
+    fn do_logs(&mut self, records: &[FastLogRecord]) {
+        let mut inner = self.inner.lock();
+        for x in records {
+            match &x.command {
+                Command::CommandRecord => {
+                    if x.level <= self.trigger {
+                        inner.dump(Some(x));
+                    } else {
+                        inner.push(x);
+                    }
+                }
+                //the wrapped appender flush and exit with the logger
+                Command::CommandExit | Command::CommandFlush(_) => {
+                    inner.appender.do_logs(std::slice::from_ref(x));
+                }
+                Command::CommandReconfigure(_) => {}
+            }
+        }
+    }
+
+    fn take_error(&mut self) -> Option<LogError> {
+        self.inner.lock().appender.take_error()
+    }
+}
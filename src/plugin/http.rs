@@ -0,0 +1,385 @@
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use crate::error::LogError;
+use crate::formats::{write_json_str, FastLogFormatJson};
+#[cfg(feature = "gzip")]
+use flate2::write::GzEncoder;
+#[cfg(feature = "gzip")]
+use flate2::Compression;
+use log::Level;
+use std::fmt::Write as FmtWrite;
+use std::io::{BufRead, BufReader, BufWriter, ErrorKind, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+/// the body shape of request
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HttpBody {
+    /// a json object per line
+    Ndjson,
+    /// elasticsearch `_bulk`,`{"index":{"_index":".."}}` before every json object
+    ElasticBulk(String),
+    /// loki push json,the streams are labeled by level and target,the line is the formatted record
+    Loki,
+}
+
+struct LokiStream {
+    level: Level,
+    target: String,
+    /// `["ts","line"],..`
+    values: String,
+}
+
+/// post the records to an http endpoint,every batch or coalesced up to the size/time limits.
+/// a failed request is retried with exponential backoff,a request body is gzipped with the feature `gzip`
+/// ```rust
+/// use fastlog::plugin::http::{HttpAppender, HttpBody};
+/// use fastlog::{AppenderConfig, Config};
+/// use std::time::Duration;
+/// let loki = HttpAppender::new("http://127.0.0.1:3100/loki/api/v1/push", HttpBody::Loki)
+///     .unwrap()
+///     .label("app", "demo")
+///     .coalesce(1024 * 1024, Duration::from_secs(1));
+/// fastlog::init(Config::new().append(AppenderConfig::new(loki))).unwrap();
+/// ```
+pub struct HttpAppender {
+    addr: String,
+    host: String,
+    path: String,
+    shape: HttpBody,
+    json: FastLogFormatJson,
+    labels: Vec<(String, String)>,
+    headers: Vec<(String, String)>,
+    max_bytes: usize,
+    max_delay: Option<Duration>,
+    max_retries: u32,
+    min_backoff: Duration,
+    max_backoff: Duration,
+    timeout: Duration,
+    #[cfg(feature = "gzip")]
+    gzip: bool,
+    body: String,
+    streams: Vec<LokiStream>,
+    /// the records and bytes waiting to be sent
+    records: usize,
+    bytes: usize,
+    first_at: Option<Instant>,
+    error: Option<LogError>,
+}
+
+impl HttpAppender {
+    /// post to an `http://` url,https is not supported,use a local agent for it
+    pub fn new(url: &str, shape: HttpBody) -> Result<Self, LogError> {
+        let rest = url.strip_prefix("http://").ok_or_else(|| {
+            LogError::from(format!("[fastlog] http url `{}` must be http://", url))
+        })?;
+        let (host, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        if host.is_empty() {
+            return Err(LogError::from(format!(
+                "[fastlog] http url `{}` has no host",
+                url
+            )));
+        }
+        //a port after the ipv6 brackets
+        let has_port = host
+            .rfind(':')
+            .is_some_and(|i| host.rfind(']').is_none_or(|j| i > j));
+        Ok(Self {
+            addr: match has_port {
+                true => host.to_string(),
+                false => format!("{}:80", host),
+            },
+            host: host.to_string(),
+            path: path.to_string(),
+            shape,
+            json: FastLogFormatJson::new(),
+            labels: vec![],
+            headers: vec![],
+            max_bytes: 5 * 1024 * 1024,
+            max_delay: None,
+            max_retries: 3,
+            min_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            timeout: Duration::from_secs(10),
+            #[cfg(feature = "gzip")]
+            gzip: false,
+            body: String::new(),
+            streams: vec![],
+            records: 0,
+            bytes: 0,
+            first_at: None,
+            error: None,
+        })
+    }
+
+    /// set the json format of `HttpBody::Ndjson` and `HttpBody::ElasticBulk`
+    pub fn json(mut self, json: FastLogFormatJson) -> Self {
+        self.json = json;
+        self
+    }
+
+    /// add a static label of `HttpBody::Loki`
+    pub fn label(mut self, key: &str, value: &str) -> Self {
+        self.labels.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// add a request header,for example `Authorization` or `X-Scope-OrgID`
+    pub fn header(mut self, key: &str, value: &str) -> Self {
+        self.headers.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// keep the records until the body reaches max_bytes,or max_delay is passed since the first kept record.
+    /// the delay is checked when a batch is appended and by `LogAppender::tick` while no batch comes,a flush sends the kept records.
+    /// default every batch is sent,and a body is at most 5MB
+    pub fn coalesce(mut self, max_bytes: usize, max_delay: Duration) -> Self {
+        self.max_bytes = max_bytes;
+        self.max_delay = Some(max_delay);
+        self
+    }
+
+    /// set the retries of a failed request and the backoff,default is 3 retries from 100ms to 10s.
+    /// a request is retried on a connection error,status 429 or 5xx
+    pub fn retry(mut self, max_retries: u32, min_backoff: Duration, max_backoff: Duration) -> Self {
+        self.max_retries = max_retries;
+        self.min_backoff = min_backoff;
+        self.max_backoff = max_backoff.max(min_backoff);
+        self
+    }
+
+    /// set the timeout of connect,write and read,default is 10s
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// gzip the request body
+    #[cfg(feature = "gzip")]
+    pub fn gzip(mut self, gzip: bool) -> Self {
+        self.gzip = gzip;
+        self
+    }
+
+    fn push(&mut self, record: &FastLogRecord) {
+        if self.first_at.is_none() {
+            self.first_at = Some(Instant::now());
+        }
+        let start = self.body.len();
+        match &self.shape {
+            HttpBody::Ndjson => {
+                self.json.write_record(&mut self.body, record);
+                self.body.push('\n');
+            }
+            HttpBody::ElasticBulk(index) => {
+                self.body.push_str("{\"index\":{\"_index\":");
+                write_json_str(&mut self.body, index, false);
+                self.body.push_str("}}\n");
+                self.json.write_record(&mut self.body, record);
+                self.body.push('\n');
+            }
+            HttpBody::Loki => {
+                let stream = match self
+                    .streams
+                    .iter_mut()
+                    .position(|v| v.level == record.level && v.target == record.target)
+                {
+                    Some(i) => &mut self.streams[i],
+                    None => {
+                        self.streams.push(LokiStream {
+                            level: record.level,
+                            target: record.target.to_string(),
+                            values: String::new(),
+                        });
+                        self.streams.last_mut().unwrap()
+                    }
+                };
+                let start = stream.values.len();
+                if !stream.values.is_empty() {
+                    stream.values.push(',');
+                }
+                let ts = record
+                    .now
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_nanos();
+                let line = match record.formated.is_empty() {
+                    true => record.args.as_ref(),
+                    false => record.formated.trim_end_matches(['\r', '\n']),
+                };
+                let _ = write!(stream.values, "[\"{}\",", ts);
+                write_json_str(&mut stream.values, line, false);
+                stream.values.push(']');
+                self.bytes += stream.values.len() - start;
+            }
+        }
+        self.bytes += self.body.len() - start;
+        self.records += 1;
+    }
+
+    /// build the loki body from the streams
+    fn build_loki(&mut self) {
+        self.body.clear();
+        self.body.push_str("{\"streams\":[");
+        for (i, stream) in self.streams.iter().enumerate() {
+            if i > 0 {
+                self.body.push(',');
+            }
+            self.body.push_str("{\"stream\":{\"level\":");
+            let level = stream.level.as_str().to_ascii_lowercase();
+            write_json_str(&mut self.body, &level, false);
+            self.body.push_str(",\"target\":");
+            write_json_str(&mut self.body, &stream.target, false);
+            for (k, v) in &self.labels {
+                self.body.push(',');
+                write_json_str(&mut self.body, k, false);
+                self.body.push(':');
+                write_json_str(&mut self.body, v, false);
+            }
+            self.body.push_str("},\"values\":[");
+            self.body.push_str(&stream.values);
+            self.body.push_str("]}");
+        }
+        self.body.push_str("]}");
+    }
+
+    /// send the kept records,they are dropped when all retries are failed
+    fn send(&mut self) {
+        if self.records == 0 {
+            return;
+        }
+        if self.shape == HttpBody::Loki {
+            self.build_loki();
+        }
+        let mut backoff = self.min_backoff;
+        let mut retries = 0;
+        loop {
+            let error = match self.post() {
+                Ok(status) if (200..300).contains(&status) => break,
+                Ok(status) => (status == 429 || status >= 500, format!("status {}", status)),
+                Err(e) => (true, e.to_string()),
+            };
+            if !error.0 || retries >= self.max_retries {
+                self.error = Some(LogError::from(format!(
+                    "http {}{}: {},{} records are dropped",
+                    self.host, self.path, error.1, self.records
+                )));
+                break;
+            }
+            std::thread::sleep(backoff);
+            backoff = (backoff * 2).min(self.max_backoff);
+            retries += 1;
+        }
+        self.body.clear();
+        self.streams.clear();
+        self.records = 0;
+        self.bytes = 0;
+        self.first_at = None;
+    }
+
+    /// post the body and read the status
+    fn post(&self) -> std::io::Result<u16> {
+        let timeout = Some(self.timeout).filter(|v| !v.is_zero());
+        let addr = self
+            .addr
+            .to_socket_addrs()?
+            .next()
+            .ok_or(ErrorKind::AddrNotAvailable)?;
+        let stream = match timeout {
+            Some(timeout) => TcpStream::connect_timeout(&addr, timeout)?,
+            None => TcpStream::connect(addr)?,
+        };
+        stream.set_write_timeout(timeout)?;
+        stream.set_read_timeout(timeout)?;
+        #[cfg(feature = "gzip")]
+        let gzip_body = match self.gzip {
+            true => {
+                let mut encoder = GzEncoder::new(vec![], Compression::default());
+                encoder.write_all(self.body.as_bytes())?;
+                Some(encoder.finish()?)
+            }
+            false => None,
+        };
+        #[cfg(not(feature = "gzip"))]
+        let gzip_body: Option<Vec<u8>> = None;
+        let body = gzip_body.as_deref().unwrap_or(self.body.as_bytes());
+        let content_type = match self.shape {
+            HttpBody::Loki => "application/json",
+            _ => "application/x-ndjson",
+        };
+        let mut head = format!(
+            "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+            self.path,
+            self.host,
+            content_type,
+            body.len()
+        );
+        if gzip_body.is_some() {
+            head.push_str("Content-Encoding: gzip\r\n");
+        }
+        for (k, v) in &self.headers {
+            let _ = write!(head, "{}: {}\r\n", k, v);
+        }
+        head.push_str("\r\n");
+        let mut writer = BufWriter::new(&stream);
+        writer.write_all(head.as_bytes())?;
+        writer.write_all(body)?;
+        writer.flush()?;
+        drop(writer);
+        let mut status = String::new();
+        BufReader::new(&stream).read_line(&mut status)?;
+        //HTTP/1.1 200 OK
+        status
+            .split(' ')
+            .nth(1)
+            .and_then(|v| v.parse().ok())
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("bad status line `{}`", status.trim_end()),
+                )
+            })
+    }
+}
+
+impl LogAppender for HttpAppender {
+    fn do_logs(&mut self, records: &[FastLogRecord]) {
+        let mut flush = false;
+        for x in records {
+            match &x.command {
+                Command::CommandRecord => {
+                    self.push(x);
+                    if self.bytes >= self.max_bytes {
+                        self.send();
+                    }
+                }
+                Command::CommandExit => flush = true,
+                Command::CommandFlush(_) => flush = true,
+                Command::CommandReconfigure(_) => {}
+            }
+        }
+        let due = match self.max_delay {
+            None => true,
+            Some(delay) => self.first_at.is_some_and(|v| v.elapsed() >= delay),
+        };
+        if flush || due {
+            self.send();
+        }
+    }
+
+    /// the time left of max_delay,when there are kept records
+    fn next_tick(&self) -> Option<Duration> {
+        let delay = self.max_delay?;
+        self.first_at.map(|v| delay.saturating_sub(v.elapsed()))
+    }
+
+    fn tick(&mut self) {
+        self.do_logs(&[]);
+    }
+
+    fn take_error(&mut self) -> Option<LogError> {
+        self.error.take()
+    }
+}
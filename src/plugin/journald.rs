@@ -0,0 +1,273 @@
+use crate::appender::{Command, FastLogRecord, FieldValue, LogAppender};
+use crate::error::LogError;
+use crate::plugin::syslog::severity;
+use rustix::fs::{fcntl_add_seals, memfd_create, MemfdFlags, SealFlags};
+use rustix::io::Errno;
+use rustix::net::{sendmsg, SendAncillaryBuffer, SendAncillaryMessage, SendFlags};
+use std::fs::File;
+use std::io::{ErrorKind, Write};
+use std::mem::MaybeUninit;
+use std::os::fd::AsFd;
+use std::os::unix::net::UnixDatagram;
+use std::time::{Duration, Instant};
+
+/// the socket of systemd-journald
+pub const JOURNALD_PATH: &str = "/run/systemd/journal/socket";
+
+/// send the records to journald by the native protocol,the fields are
+/// `PRIORITY`,`MESSAGE`,`CODE_FILE`,`CODE_LINE`,`CODE_MODULE`,`TARGET`,`SYSLOG_IDENTIFIER` and the key-values uppercased.
+/// an entry which is too large for a datagram is sent by a sealed memfd.
+/// when journald is restarted the socket is connected again with backoff and the records are dropped meanwhile
+pub struct JournaldAppender {
+    path: String,
+    socket: Option<UnixDatagram>,
+    min_backoff: Duration,
+    max_backoff: Duration,
+    backoff: Duration,
+    next_connect: Option<Instant>,
+    identifier: String,
+    buf: Vec<u8>,
+    error: Option<LogError>,
+}
+
+impl JournaldAppender {
+    /// connect to `JOURNALD_PATH`
+    pub fn new() -> Result<Self, LogError> {
+        Self::with_path(JOURNALD_PATH)
+    }
+
+    /// connect to a journald socket
+    pub fn with_path(path: &str) -> Result<Self, LogError> {
+        let socket = open(path)?;
+        Ok(Self {
+            path: path.to_string(),
+            socket: Some(socket),
+            min_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(30),
+            backoff: Duration::from_millis(100),
+            next_connect: None,
+            identifier: std::env::current_exe()
+                .ok()
+                .and_then(|v| Some(v.file_name()?.to_string_lossy().to_string()))
+                .unwrap_or_default(),
+            buf: vec![],
+            error: None,
+        })
+    }
+
+    /// set `SYSLOG_IDENTIFIER`,default is the name of executable
+    pub fn identifier(mut self, identifier: &str) -> Self {
+        self.identifier = identifier.to_string();
+        self
+    }
+
+    /// set the backoff of reconnect,it doubles from min to max after every failure,default is 100ms to 30s
+    pub fn backoff(mut self, min: Duration, max: Duration) -> Self {
+        self.min_backoff = min;
+        self.max_backoff = max.max(min);
+        self.backoff = min;
+        self
+    }
+
+    /// connect when it is disconnected and the backoff is passed
+    fn connect(&mut self) -> bool {
+        if self.socket.is_some() {
+            return true;
+        }
+        if self.next_connect.is_some_and(|v| Instant::now() < v) {
+            return false;
+        }
+        match open(&self.path) {
+            Ok(socket) => {
+                self.socket = Some(socket);
+                self.backoff = self.min_backoff;
+                self.next_connect = None;
+                true
+            }
+            Err(e) => {
+                self.disconnect(e);
+                false
+            }
+        }
+    }
+
+    /// drop the socket and wait for the backoff
+    fn disconnect(&mut self, e: std::io::Error) {
+        self.socket = None;
+        self.next_connect = Some(Instant::now() + self.backoff);
+        self.backoff = (self.backoff * 2).min(self.max_backoff);
+        self.error = Some(LogError::from(format!("journald {:?}: {}", self.path, e)));
+    }
+
+    /// send the entry,a socket whose journald is gone is connected again once without backoff
+    fn send_or_reopen(&mut self) -> std::io::Result<()> {
+        match self.send() {
+            Err(e) if is_broken(&e) => {
+                self.socket = None;
+                self.socket = Some(open(&self.path)?);
+                self.send()
+            }
+            result => result,
+        }
+    }
+
+    fn encode(&mut self, record: &FastLogRecord) {
+        self.buf.clear();
+        let priority = [b'0' + severity(record.level)];
+        write_field(&mut self.buf, "PRIORITY", &priority);
+        write_field(&mut self.buf, "MESSAGE", record.args.as_bytes());
+        if !record.file.is_empty() {
+            write_field(&mut self.buf, "CODE_FILE", record.file.as_bytes());
+        }
+        if let Some(line) = record.line {
+            write_field(&mut self.buf, "CODE_LINE", line.to_string().as_bytes());
+        }
+        if !record.module_path.is_empty() {
+            write_field(&mut self.buf, "CODE_MODULE", record.module_path.as_bytes());
+        }
+        write_field(&mut self.buf, "TARGET", record.target.as_bytes());
+        if !self.identifier.is_empty() {
+            write_field(
+                &mut self.buf,
+                "SYSLOG_IDENTIFIER",
+                self.identifier.as_bytes(),
+            );
+        }
+        let mut name = String::new();
+        for (k, v) in &record.fields {
+            field_name(&mut name, k);
+            //a key-value can not replace a field written by the appender
+            if OWN_FIELDS.contains(&name.as_str()) {
+                name.insert_str(0, "KV_");
+            }
+            match v {
+                FieldValue::Str(v) => write_field(&mut self.buf, &name, v.as_bytes()),
+                v => write_field(&mut self.buf, &name, v.to_string().as_bytes()),
+            }
+        }
+    }
+
+    fn send(&self) -> std::io::Result<()> {
+        let socket = self.socket.as_ref().ok_or(ErrorKind::NotConnected)?;
+        match rustix::net::send(socket, &self.buf, SendFlags::empty()) {
+            Ok(_) => Ok(()),
+            Err(Errno::MSGSIZE) | Err(Errno::NOBUFS) => self.send_memfd(socket),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// write the entry into a sealed memfd and send the fd
+    fn send_memfd(&self, socket: &UnixDatagram) -> std::io::Result<()> {
+        let fd = memfd_create(
+            "fastlog-journald",
+            MemfdFlags::CLOEXEC | MemfdFlags::ALLOW_SEALING,
+        )
+        .map_err(std::io::Error::from)?;
+        let mut file = File::from(fd);
+        file.write_all(&self.buf)?;
+        fcntl_add_seals(
+            &file,
+            SealFlags::SHRINK | SealFlags::GROW | SealFlags::WRITE | SealFlags::SEAL,
+        )
+        .map_err(std::io::Error::from)?;
+        let fds = [file.as_fd()];
+        let mut space = [MaybeUninit::uninit(); rustix::cmsg_space!(ScmRights(1))];
+        let mut control = SendAncillaryBuffer::new(&mut space);
+        control.push(SendAncillaryMessage::ScmRights(&fds));
+        sendmsg(socket, &[], &mut control, SendFlags::empty()).map_err(std::io::Error::from)?;
+        Ok(())
+    }
+}
+
+impl LogAppender for JournaldAppender {
+    fn do_logs(&mut self, records: &[FastLogRecord]) {
+        let mut dropped = 0;
+        for x in records {
+            if x.command != Command::CommandRecord {
+                continue;
+            }
+            if !self.connect() {
+                dropped += 1;
+                continue;
+            }
+            self.encode(x);
+            match self.send_or_reopen() {
+                Ok(()) => {}
+                Err(e) if self.socket.is_none() || is_broken(&e) => {
+                    self.disconnect(e);
+                    dropped += 1;
+                }
+                Err(e) => {
+                    self.error = Some(LogError::from(format!("journald: {}", e)));
+                }
+            }
+        }
+        if dropped > 0 && self.error.is_none() {
+            self.error = Some(LogError::from(format!(
+                "journald {:?}: disconnected,{} records are dropped",
+                self.path, dropped
+            )));
+        }
+    }
+
+    fn take_error(&mut self) -> Option<LogError> {
+        self.error.take()
+    }
+}
+
+fn open(path: &str) -> std::io::Result<UnixDatagram> {
+    let socket = UnixDatagram::unbound()?;
+    socket.connect(path)?;
+    Ok(socket)
+}
+
+/// journald is stopped or restarted
+fn is_broken(e: &std::io::Error) -> bool {
+    matches!(
+        e.kind(),
+        ErrorKind::ConnectionRefused | ErrorKind::NotFound | ErrorKind::NotConnected
+    )
+}
+
+/// write `NAME=value\n`,or `NAME\n` + little endian u64 length + value + `\n` when the value has a new line
+fn write_field(buf: &mut Vec<u8>, name: &str, value: &[u8]) {
+    buf.extend_from_slice(name.as_bytes());
+    if value.contains(&b'\n') {
+        buf.push(b'\n');
+        buf.extend_from_slice(&(value.len() as u64).to_le_bytes());
+    } else {
+        buf.push(b'=');
+    }
+    buf.extend_from_slice(value);
+    buf.push(b'\n');
+}
+
+/// the fields written by the appender,a key-value with the same name is written as `KV_<NAME>`
+const OWN_FIELDS: [&str; 7] = [
+    "PRIORITY",
+    "MESSAGE",
+    "CODE_FILE",
+    "CODE_LINE",
+    "CODE_MODULE",
+    "TARGET",
+    "SYSLOG_IDENTIFIER",
+];
+
+/// a field name is uppercase letters,digits and `_`,it can not start with `_` or a digit,at most 64 chars
+fn field_name(name: &mut String, key: &str) {
+    name.clear();
+    for c in key.chars() {
+        match c.to_ascii_uppercase() {
+            c @ ('A'..='Z' | '0'..='9') => name.push(c),
+            _ if !name.is_empty() => name.push('_'),
+            _ => {}
+        }
+    }
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert_str(0, "F_");
+    }
+    if name.is_empty() {
+        name.push_str("FIELD");
+    }
+    name.truncate(64);
+}
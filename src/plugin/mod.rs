@@ -1,6 +1,15 @@
 pub mod console;
 pub mod file;
+pub mod file_dynamic;
 pub mod file_loop;
 pub mod file_name;
 pub mod file_split;
+pub mod flight_recorder;
+pub mod http;
+#[cfg(target_os = "linux")]
+pub mod journald;
+pub mod network;
 pub mod packer;
+pub mod router;
+pub mod subscriber;
+pub mod syslog;
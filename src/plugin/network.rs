@@ -0,0 +1,403 @@
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use crate::error::LogError;
+use std::collections::VecDeque;
+use std::io::{ErrorKind, Write};
+use std::net::{TcpStream, ToSocketAddrs, UdpSocket};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// the framing of a stream
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Framing {
+    /// a record ends with `\n`,it is added when the formatted record does not end with it
+    #[default]
+    Newline,
+    /// a big endian u32 length before every record
+    LengthPrefixed,
+}
+
+/// the address of collector
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NetworkTarget {
+    Tcp(String),
+    /// a datagram per record,the records are not buffered
+    Udp(String),
+    /// a unix stream socket
+    #[cfg(unix)]
+    Unix(String),
+}
+
+/// the counters of `NetworkAppender`,they can be read after the appender is moved into the logger
+#[derive(Debug, Default)]
+pub struct NetworkCounters {
+    pub sent_bytes: AtomicU64,
+    pub sent_records: AtomicU64,
+    pub dropped_bytes: AtomicU64,
+    pub dropped_records: AtomicU64,
+    /// the bytes waiting for the connection
+    pub buffered_bytes: AtomicU64,
+    pub connects: AtomicU64,
+}
+
+/// the error means the peer is gone and the connection must be opened again,
+/// other errors(for example EMSGSIZE of a large datagram) only fail the record
+pub fn is_disconnected(e: &std::io::Error) -> bool {
+    matches!(
+        e.kind(),
+        ErrorKind::ConnectionRefused
+            | ErrorKind::NotConnected
+            | ErrorKind::BrokenPipe
+            | ErrorKind::ConnectionReset
+    )
+}
+
+enum Conn {
+    Tcp(TcpStream),
+    Udp(UdpSocket),
+    #[cfg(unix)]
+    Unix(UnixStream),
+}
+
+/// stream the formatted records to a collector over tcp,udp or a unix socket.
+/// the connection is made again with backoff,the records are kept in a bounded buffer while it is disconnected
+/// ```rust
+/// use fastlog::plugin::network::{Framing, NetworkAppender};
+/// use fastlog::{AppenderConfig, Config};
+/// use std::time::Duration;
+/// let network = NetworkAppender::tcp("127.0.0.1:5170")
+///     .framing(Framing::Newline)
+///     .buffer_size(4 * 1024 * 1024)
+///     .backoff(Duration::from_millis(100), Duration::from_secs(30));
+/// let counters = network.counters();
+/// fastlog::init(Config::new().append(AppenderConfig::new(network))).unwrap();
+/// log::info!("hello");
+/// # fastlog::flush().unwrap().wait();
+/// println!("dropped bytes {}", counters.dropped_bytes.load(std::sync::atomic::Ordering::Relaxed));
+/// ```
+pub struct NetworkAppender {
+    target: NetworkTarget,
+    framing: Framing,
+    buffer_size: usize,
+    min_backoff: Duration,
+    max_backoff: Duration,
+    timeout: Duration,
+    conn: Option<Conn>,
+    backoff: Duration,
+    next_connect: Option<Instant>,
+    /// the framed records which are not written
+    pending: Vec<u8>,
+    /// the end offset of every record in `pending`
+    ends: VecDeque<usize>,
+    counters: Arc<NetworkCounters>,
+    error: Option<LogError>,
+}
+
+impl NetworkAppender {
+    pub fn new(target: NetworkTarget) -> Self {
+        Self {
+            target,
+            framing: Framing::default(),
+            buffer_size: 1024 * 1024,
+            min_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(30),
+            timeout: Duration::from_secs(5),
+            conn: None,
+            backoff: Duration::from_millis(100),
+            next_connect: None,
+            pending: vec![],
+            ends: VecDeque::new(),
+            counters: Arc::new(NetworkCounters::default()),
+            error: None,
+        }
+    }
+
+    /// stream to tcp,for example `127.0.0.1:5170`
+    pub fn tcp(addr: &str) -> Self {
+        Self::new(NetworkTarget::Tcp(addr.to_string()))
+    }
+
+    /// send a datagram per record to udp
+    pub fn udp(addr: &str) -> Self {
+        Self::new(NetworkTarget::Udp(addr.to_string()))
+    }
+
+    /// stream to a unix socket
+    #[cfg(unix)]
+    pub fn unix(path: &str) -> Self {
+        Self::new(NetworkTarget::Unix(path.to_string()))
+    }
+
+    /// set the framing of stream,default is `Framing::Newline`
+    pub fn framing(mut self, framing: Framing) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    /// set the max bytes buffered while it is disconnected,default is 1MB.
+    /// a record is dropped when the buffer is full and it can not be written
+    pub fn buffer_size(mut self, buffer_size: usize) -> Self {
+        self.buffer_size = buffer_size;
+        self
+    }
+
+    /// set the backoff of reconnect,it doubles from min to max after every failure,default is 100ms to 30s
+    pub fn backoff(mut self, min: Duration, max: Duration) -> Self {
+        self.min_backoff = min;
+        self.max_backoff = max.max(min);
+        self.backoff = min;
+        self
+    }
+
+    /// set the timeout of connect and write,default is 5s
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// the counters
+    pub fn counters(&self) -> Arc<NetworkCounters> {
+        self.counters.clone()
+    }
+
+    /// connect when it is disconnected and the backoff is passed
+    fn connect(&mut self) -> bool {
+        if self.conn.is_some() {
+            return true;
+        }
+        if self.next_connect.is_some_and(|v| Instant::now() < v) {
+            return false;
+        }
+        match self.open() {
+            Ok(conn) => {
+                self.conn = Some(conn);
+                self.backoff = self.min_backoff;
+                self.next_connect = None;
+                self.counters.connects.fetch_add(1, Ordering::Relaxed);
+                true
+            }
+            Err(e) => {
+                self.disconnect(e);
+                false
+            }
+        }
+    }
+
+    fn open(&self) -> std::io::Result<Conn> {
+        let timeout = Some(self.timeout).filter(|v| !v.is_zero());
+        match &self.target {
+            NetworkTarget::Tcp(addr) => {
+                let mut last = None;
+                for addr in addr.to_socket_addrs()? {
+                    let stream = match timeout {
+                        Some(timeout) => TcpStream::connect_timeout(&addr, timeout),
+                        None => TcpStream::connect(addr),
+                    };
+                    match stream {
+                        Ok(stream) => {
+                            stream.set_write_timeout(timeout)?;
+                            stream.set_nodelay(true)?;
+                            return Ok(Conn::Tcp(stream));
+                        }
+                        Err(e) => last = Some(e),
+                    }
+                }
+                Err(last.unwrap_or_else(|| ErrorKind::AddrNotAvailable.into()))
+            }
+            NetworkTarget::Udp(addr) => {
+                let socket = UdpSocket::bind("0.0.0.0:0")?;
+                socket.connect(addr)?;
+                Ok(Conn::Udp(socket))
+            }
+            #[cfg(unix)]
+            NetworkTarget::Unix(path) => {
+                let stream = UnixStream::connect(path)?;
+                stream.set_write_timeout(timeout)?;
+                Ok(Conn::Unix(stream))
+            }
+        }
+    }
+
+    /// drop the connection and wait for the backoff
+    fn disconnect(&mut self, e: std::io::Error) {
+        self.conn = None;
+        self.next_connect = Some(Instant::now() + self.backoff);
+        self.backoff = (self.backoff * 2).min(self.max_backoff);
+        self.error = Some(LogError::from(format!("network {:?}: {}", self.target, e)));
+    }
+
+    fn drop_record(&self, bytes: usize) {
+        self.counters
+            .dropped_bytes
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+        self.counters
+            .dropped_records
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// append a framed record to the pending buffer,it is dropped when the buffer is full
+    fn push(&mut self, record: &str) {
+        let newline = self.framing == Framing::Newline && !record.ends_with('\n');
+        let len = match self.framing {
+            Framing::Newline => record.len() + newline as usize,
+            Framing::LengthPrefixed => record.len() + 4,
+        };
+        if !self.pending.is_empty() && self.pending.len() + len > self.buffer_size {
+            self.write_pending();
+            if !self.pending.is_empty() && self.pending.len() + len > self.buffer_size {
+                self.drop_record(len);
+                return;
+            }
+        }
+        if self.framing == Framing::LengthPrefixed {
+            self.pending
+                .extend_from_slice(&(record.len() as u32).to_be_bytes());
+        }
+        self.pending.extend_from_slice(record.as_bytes());
+        if newline {
+            self.pending.push(b'\n');
+        }
+        self.ends.push_back(self.pending.len());
+    }
+
+    /// write the pending buffer,a partly written record is dropped when the connection is broken
+    fn write_pending(&mut self) {
+        if self.pending.is_empty() || !self.connect() {
+            return;
+        }
+        let mut written = 0;
+        let mut result = Ok(());
+        while written < self.pending.len() {
+            let n = match self.conn.as_mut() {
+                Some(Conn::Tcp(v)) => v.write(&self.pending[written..]),
+                #[cfg(unix)]
+                Some(Conn::Unix(v)) => v.write(&self.pending[written..]),
+                _ => Ok(0),
+            };
+            match n {
+                Ok(0) => {
+                    result = Err(ErrorKind::WriteZero.into());
+                    break;
+                }
+                Ok(n) => written += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+        let (records, end) = self.frames(written);
+        //the records written and the partly written one are removed
+        let removed = self.ends.partition_point(|v| *v <= end);
+        self.ends.drain(..removed);
+        for v in self.ends.iter_mut() {
+            *v -= end;
+        }
+        self.counters
+            .sent_bytes
+            .fetch_add(written as u64, Ordering::Relaxed);
+        self.counters
+            .sent_records
+            .fetch_add(records, Ordering::Relaxed);
+        if let Err(e) = result {
+            if end > written {
+                self.drop_record(end - written);
+            }
+            self.disconnect(e);
+        }
+        self.pending.drain(..end);
+    }
+
+    /// the count of records and the end of the record which contains `written`,by the record ends
+    fn frames(&self, written: usize) -> (u64, usize) {
+        let records = self.ends.partition_point(|v| *v <= written);
+        let full = match records {
+            0 => 0,
+            n => self.ends[n - 1],
+        };
+        let end = match self.ends.get(records) {
+            Some(v) if written > full => *v,
+            _ => full,
+        };
+        (records as u64, end)
+    }
+
+    fn send_datagrams(&mut self, records: &[FastLogRecord]) {
+        for x in records {
+            if x.command != Command::CommandRecord || x.formated.is_empty() {
+                continue;
+            }
+            if !self.connect() {
+                self.drop_record(x.formated.len());
+                continue;
+            }
+            let result = match self.conn.as_ref() {
+                Some(Conn::Udp(v)) => v.send(x.formated.as_bytes()),
+                _ => Ok(0),
+            };
+            match result {
+                Ok(n) => {
+                    self.counters
+                        .sent_bytes
+                        .fetch_add(n as u64, Ordering::Relaxed);
+                    self.counters.sent_records.fetch_add(1, Ordering::Relaxed);
+                }
+                Err(e) if is_disconnected(&e) => {
+                    self.drop_record(x.formated.len());
+                    self.disconnect(e);
+                }
+                //for example EMSGSIZE,the socket is kept
+                Err(e) => {
+                    self.drop_record(x.formated.len());
+                    self.error = Some(LogError::from(format!(
+                        "network {:?}: a record of {} bytes is dropped: {}",
+                        self.target,
+                        x.formated.len(),
+                        e
+                    )));
+                }
+            }
+        }
+    }
+}
+
+impl LogAppender for NetworkAppender {
+    fn do_logs(&mut self, records: &[FastLogRecord]) {
+        if let NetworkTarget::Udp(_) = self.target {
+            self.send_datagrams(records);
+            return;
+        }
+        for x in records {
+            if x.command == Command::CommandRecord && !x.formated.is_empty() {
+                self.push(&x.formated);
+            }
+        }
+        self.write_pending();
+        self.counters
+            .buffered_bytes
+            .store(self.pending.len() as u64, Ordering::Relaxed);
+    }
+
+    fn take_error(&mut self) -> Option<LogError> {
+        self.error.take()
+    }
+
+    /// the buffered records are written again after the backoff,without waiting the next batch
+    fn next_tick(&self) -> Option<Duration> {
+        if self.pending.is_empty() {
+            return None;
+        }
+        let wait = match self.next_connect {
+            Some(v) => v.saturating_duration_since(Instant::now()),
+            None => self.min_backoff,
+        };
+        Some(wait.max(Duration::from_millis(1)))
+    }
+
+    fn tick(&mut self) {
+        self.do_logs(&[]);
+    }
+}
@@ -0,0 +1,191 @@
+use crate::appender::{Command, FastLogRecord, FieldValue, LogAppender};
+use crate::error::LogError;
+use crate::pool::buffer_pool;
+use log::Level;
+
+/// a rule of `RouterAppender`
+#[derive(Clone, Debug, PartialEq)]
+pub enum RouteRule {
+    /// the target starts with the prefix,for example `audit`
+    Target(String),
+    /// the module_path matches the glob,`*` matches any chars and `?` matches a char,for example `app::db::*`
+    Module(String),
+    /// the level is between the two levels,for example `RouteRule::Level(Level::Error, Level::Warn)`
+    Level(Level, Level),
+    /// the key-value is equal to the value,a value that is not a string is compared by its `Display`
+    Field(String, String),
+    /// the record has the key-value
+    HasField(String),
+    All(Vec<RouteRule>),
+    Any(Vec<RouteRule>),
+    Not(Box<RouteRule>),
+}
+
+impl RouteRule {
+    pub fn matches(&self, record: &FastLogRecord) -> bool {
+        match self {
+            RouteRule::Target(prefix) => record.target.starts_with(prefix.as_str()),
+            RouteRule::Module(glob) => glob_match(glob.as_bytes(), record.module_path.as_bytes()),
+            RouteRule::Level(a, b) => *a.min(b) <= record.level && record.level <= *a.max(b),
+            RouteRule::Field(key, value) => match record.field(key) {
+                Some(FieldValue::Str(v)) => v == value,
+                Some(v) => v.to_string() == *value,
+                None => false,
+            },
+            RouteRule::HasField(key) => record.field(key).is_some(),
+            RouteRule::All(rules) => rules.iter().all(|v| v.matches(record)),
+            RouteRule::Any(rules) => rules.iter().any(|v| v.matches(record)),
+            RouteRule::Not(rule) => !rule.matches(record),
+        }
+    }
+}
+
+struct Route {
+    rule: Option<RouteRule>,
+    appender: Box<dyn LogAppender>,
+    batch: Vec<FastLogRecord>,
+}
+
+/// dispatch every record to the first route whose rule matches,or the default route.
+/// every child gets its records in a single batch,flush and exit commands go to all children
+/// ```rust
+/// use fastlog::plugin::file::FileAppender;
+/// use fastlog::plugin::router::{RouteRule, RouterAppender};
+/// use fastlog::{AppenderConfig, Config};
+/// let router = RouterAppender::new()
+///     .route(RouteRule::Target("audit".to_string()), FileAppender::new("target/logs/audit.log").unwrap())
+///     .default_route(FileAppender::new("target/logs/app.log").unwrap());
+/// fastlog::init(Config::new().append(AppenderConfig::new(router))).unwrap();
+/// log::info!(target: "audit", "user login");
+/// ```
+pub struct RouterAppender {
+    /// the default route is the last one
+    routes: Vec<Route>,
+    default: bool,
+    /// the route index of every record,`None` for the commands
+    targets: Vec<Option<usize>>,
+}
+
+impl Default for RouterAppender {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RouterAppender {
+    /// a router without routes,the records that match no route are dropped
+    pub fn new() -> Self {
+        Self {
+            routes: vec![],
+            default: false,
+            targets: vec![],
+        }
+    }
+
+    /// add a route,the routes are matched in order
+    pub fn route<A: LogAppender + 'static>(mut self, rule: RouteRule, appender: A) -> Self {
+        let at = self.routes.len() - self.default as usize;
+        self.routes.insert(
+            at,
+            Route {
+                rule: Some(rule),
+                appender: Box::new(appender),
+                batch: vec![],
+            },
+        );
+        self
+    }
+
+    /// set the route of the records that match no route
+    pub fn default_route<A: LogAppender + 'static>(mut self, appender: A) -> Self {
+        if self.default {
+            self.routes.pop();
+        }
+        self.routes.push(Route {
+            rule: None,
+            appender: Box::new(appender),
+            batch: vec![],
+        });
+        self.default = true;
+        self
+    }
+
+    fn route_of(&self, record: &FastLogRecord) -> Option<usize> {
+        self.routes.iter().position(|v| match &v.rule {
+            Some(rule) => rule.matches(record),
+            None => true,
+        })
+    }
+}
+
+impl LogAppender for RouterAppender {
+    fn do_logs(&mut self, records: &[FastLogRecord]) {
+        let mut targets = std::mem::take(&mut self.targets);
+        targets.clear();
+        //a dropped record is `Some(usize::MAX)`
+        targets.extend(records.iter().map(|x| match x.command {
+            Command::CommandRecord => Some(self.route_of(x).unwrap_or(usize::MAX)),
+            _ => None,
+        }));
+        //all records go to a route,it gets the batch without copy
+        let mut single = targets.iter().flatten();
+        let single = single
+            .next()
+            .filter(|first| single.all(|v| v == *first))
+            .copied();
+        for (i, route) in self.routes.iter_mut().enumerate() {
+            if single == Some(i) {
+                route.appender.do_logs(records);
+                continue;
+            }
+            for (x, target) in records.iter().zip(targets.iter()) {
+                if target.is_none() || *target == Some(i) {
+                    route.batch.push(x.clone());
+                }
+            }
+            if !route.batch.is_empty() {
+                route.appender.do_logs(&route.batch);
+            }
+            for x in route.batch.drain(..) {
+                buffer_pool().recycle(x);
+            }
+        }
+        self.targets = targets;
+    }
+
+    fn take_error(&mut self) -> Option<LogError> {
+        let errors: Vec<String> = self
+            .routes
+            .iter_mut()
+            .filter_map(|v| v.appender.take_error())
+            .map(|v| v.to_string())
+            .collect();
+        match errors.is_empty() {
+            true => None,
+            false => Some(LogError::from(errors.join(","))),
+        }
+    }
+}
+
+/// match the glob,`*` matches any chars and `?` matches a char
+fn glob_match(glob: &[u8], v: &[u8]) -> bool {
+    let (mut g, mut i) = (0, 0);
+    //the position after the last `*` and the matched position of it
+    let mut star = None;
+    while i < v.len() {
+        if g < glob.len() && (glob[g] == b'?' || glob[g] == v[i]) {
+            g += 1;
+            i += 1;
+        } else if g < glob.len() && glob[g] == b'*' {
+            g += 1;
+            star = Some((g, i));
+        } else if let Some((sg, si)) = star {
+            g = sg;
+            i = si + 1;
+            star = Some((sg, si + 1));
+        } else {
+            return false;
+        }
+    }
+    glob[g..].iter().all(|v| *v == b'*')
+}
@@ -0,0 +1,138 @@
+use crate::appender::{Command, FastLogRecord, LogAppender};
+use crate::filter::Filter;
+use crate::{chan, Receiver, Sender};
+use crossbeam_channel::TrySendError;
+use log::LevelFilter;
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+struct Subscriber {
+    sender: Sender<FastLogRecord>,
+    level: LevelFilter,
+    filter: Option<Box<dyn Filter>>,
+}
+
+#[derive(Default)]
+struct Inner {
+    subscribers: Mutex<Vec<Subscriber>>,
+    dropped: AtomicU64,
+}
+
+/// a handle of `SubscriberAppender`,it registers subscribers at any time
+#[derive(Clone, Default)]
+pub struct Subscribers {
+    inner: Arc<Inner>,
+}
+
+impl Subscribers {
+    /// subscribe the records at or above the level,at most `capacity` records are queued.
+    /// the records are dropped while the queue is full,and the subscriber is removed after the receiver is dropped
+    pub fn subscribe(&self, capacity: usize, level: LevelFilter) -> Receiver<FastLogRecord> {
+        self.add(capacity, level, None)
+    }
+
+    /// subscribe the records at or above the level which the filter accepts
+    pub fn subscribe_filter<F: Filter + 'static>(
+        &self,
+        capacity: usize,
+        level: LevelFilter,
+        filter: F,
+    ) -> Receiver<FastLogRecord> {
+        self.add(capacity, level, Some(Box::new(filter)))
+    }
+
+    fn add(
+        &self,
+        capacity: usize,
+        level: LevelFilter,
+        filter: Option<Box<dyn Filter>>,
+    ) -> Receiver<FastLogRecord> {
+        let (sender, receiver) = chan(Some(capacity.max(1)));
+        self.inner.subscribers.lock().push(Subscriber {
+            sender,
+            level,
+            filter,
+        });
+        receiver
+    }
+
+    /// the count of subscribers,a dropped receiver is counted until a record is sent to it
+    pub fn len(&self) -> usize {
+        self.inner.subscribers.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// the count of records dropped for the full queues
+    pub fn dropped(&self) -> u64 {
+        self.inner.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// fan the records out to the subscribers in the process,for example a live tail of an admin page.
+/// a slow subscriber loses records instead of blocking the logger
+/// ```rust
+/// use fastlog::plugin::subscriber::SubscriberAppender;
+/// use fastlog::{AppenderConfig, Config};
+/// use log::LevelFilter;
+/// let appender = SubscriberAppender::new();
+/// let subscribers = appender.subscribers();
+/// fastlog::init(Config::new().append(AppenderConfig::new(appender))).unwrap();
+/// let tail = subscribers.subscribe(1000, LevelFilter::Warn);
+/// log::warn!("disk is full");
+/// fastlog::flush().unwrap().wait();
+/// for record in tail.try_iter() {
+///     print!("{}", record.formated);
+/// }
+/// ```
+#[derive(Default)]
+pub struct SubscriberAppender {
+    inner: Arc<Inner>,
+}
+
+impl SubscriberAppender {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// a handle for `Subscribers::subscribe`
+    pub fn subscribers(&self) -> Subscribers {
+        Subscribers {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl LogAppender for SubscriberAppender {
+    fn do_logs(&mut self, records: &[FastLogRecord]) {
+        let mut subscribers = self.inner.subscribers.lock();
+        if subscribers.is_empty() {
+            return;
+        }
+        let mut dropped = 0;
+        for x in records {
+            if x.command != Command::CommandRecord {
+                continue;
+            }
+            subscribers.retain(|s| {
+                if x.level > s.level || s.filter.as_ref().is_some_and(|f| !f.do_log_record(x)) {
+                    return true;
+                }
+                match s.sender.try_send(x.clone()) {
+                    Ok(_) => true,
+                    Err(TrySendError::Full(_)) => {
+                        dropped += 1;
+                        true
+                    }
+                    Err(TrySendError::Disconnected(_)) => false,
+                }
+            });
+        }
+        if dropped > 0 {
+            self.inner.dropped.fetch_add(dropped, Ordering::Relaxed);
+        }
+    }
+}
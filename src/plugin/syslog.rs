@@ -0,0 +1,488 @@
+use crate::appender::{Command, FastLogRecord, FieldValue, LogAppender};
+use crate::error::LogError;
+use crate::formats::write_kv;
+use crate::plugin::network::is_disconnected;
+use log::Level;
+use std::fmt::Write as FmtWrite;
+use std::io::{BufWriter, ErrorKind, Write};
+use std::net::{TcpStream, ToSocketAddrs, UdpSocket};
+#[cfg(unix)]
+use std::os::unix::net::UnixDatagram;
+use std::time::{Duration, Instant};
+
+/// the syslog facility
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Facility {
+    Kern = 0,
+    User = 1,
+    Mail = 2,
+    Daemon = 3,
+    Auth = 4,
+    Syslog = 5,
+    Lpr = 6,
+    News = 7,
+    Uucp = 8,
+    Cron = 9,
+    AuthPriv = 10,
+    Ftp = 11,
+    Local0 = 16,
+    Local1 = 17,
+    Local2 = 18,
+    Local3 = 19,
+    Local4 = 20,
+    Local5 = 21,
+    Local6 = 22,
+    Local7 = 23,
+}
+
+/// the syslog message format
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyslogFormat {
+    /// `<PRI>1 TIMESTAMP HOSTNAME APP-NAME PROCID MSGID [SD] MSG`,the key-values are the structured data
+    Rfc5424,
+    /// `<PRI>Mmm dd hh:mm:ss HOSTNAME TAG[PID]: MSG`,the key-values are appended to MSG as `k=v`
+    Rfc3164,
+}
+
+/// the severity of level
+pub fn severity(level: Level) -> u8 {
+    match level {
+        Level::Error => 3,
+        Level::Warn => 4,
+        Level::Info => 6,
+        Level::Debug => 7,
+        Level::Trace => 7,
+    }
+}
+
+#[derive(Debug)]
+enum Transport {
+    #[cfg(unix)]
+    Unix(String),
+    Udp(String),
+    /// octet-counting framing(RFC 6587)
+    Tcp(String),
+}
+
+enum Conn {
+    #[cfg(unix)]
+    Unix(UnixDatagram),
+    Udp(UdpSocket),
+    Tcp(BufWriter<TcpStream>),
+}
+
+impl Conn {
+    fn set_write_timeout(&self, timeout: Option<Duration>) -> std::io::Result<()> {
+        match self {
+            #[cfg(unix)]
+            Conn::Unix(v) => v.set_write_timeout(timeout),
+            Conn::Udp(v) => v.set_write_timeout(timeout),
+            Conn::Tcp(v) => v.get_ref().set_write_timeout(timeout),
+        }
+    }
+}
+
+/// send the records to syslog over the local unix socket,udp or tcp.
+/// a broken connection is opened again at once(for example `/dev/log` is recreated),
+/// if that fails it is opened again with backoff and the records are dropped meanwhile
+/// ```rust
+/// use fastlog::plugin::syslog::{Facility, SyslogAppender, SyslogFormat};
+/// use fastlog::{AppenderConfig, Config};
+/// # fn main() -> Result<(), fastlog::error::LogError> {
+/// # if std::path::Path::new("/dev/log").exists() {
+/// let syslog = SyslogAppender::unix("/dev/log")?
+///     .format(SyslogFormat::Rfc5424)
+///     .facility(Facility::Local0)
+///     .app_name("app");
+/// fastlog::init(Config::new().append(AppenderConfig::new(syslog)))?;
+/// # }
+/// # Ok(())
+/// # }
+/// ```
+pub struct SyslogAppender {
+    transport: Transport,
+    conn: Option<Conn>,
+    timeout: Duration,
+    min_backoff: Duration,
+    max_backoff: Duration,
+    backoff: Duration,
+    next_connect: Option<Instant>,
+    format: SyslogFormat,
+    facility: Facility,
+    hostname: String,
+    app_name: String,
+    procid: String,
+    sd_id: String,
+    buf: String,
+    error: Option<LogError>,
+}
+
+impl SyslogAppender {
+    fn new(transport: Transport) -> Self {
+        let hostname = std::fs::read_to_string("/proc/sys/kernel/hostname")
+            .or_else(|_| std::fs::read_to_string("/etc/hostname"))
+            .ok()
+            .or_else(|| std::env::var("HOSTNAME").ok())
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| "localhost".to_string());
+        let app_name = std::env::current_exe()
+            .ok()
+            .and_then(|v| Some(v.file_name()?.to_string_lossy().to_string()))
+            .unwrap_or_else(|| "fastlog".to_string());
+        Self {
+            transport,
+            conn: None,
+            timeout: Duration::from_secs(5),
+            min_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(30),
+            backoff: Duration::from_millis(100),
+            next_connect: None,
+            format: SyslogFormat::Rfc5424,
+            facility: Facility::User,
+            hostname,
+            app_name,
+            procid: std::process::id().to_string(),
+            sd_id: "fields@32473".to_string(),
+            buf: String::new(),
+            error: None,
+        }
+    }
+
+    /// connect the transport,it fails when the first connection fails
+    fn connected(transport: Transport) -> Result<Self, LogError> {
+        let mut appender = Self::new(transport);
+        appender.conn = Some(appender.open()?);
+        Ok(appender)
+    }
+
+    /// send to a local unix datagram socket,for example `/dev/log`
+    #[cfg(unix)]
+    pub fn unix(path: &str) -> Result<Self, LogError> {
+        Self::connected(Transport::Unix(path.to_string()))
+    }
+
+    /// send to udp,for example `127.0.0.1:514`
+    pub fn udp(addr: &str) -> Result<Self, LogError> {
+        Self::connected(Transport::Udp(addr.to_string()))
+    }
+
+    /// send to tcp with octet-counting framing,for example `127.0.0.1:601`
+    pub fn tcp(addr: &str) -> Result<Self, LogError> {
+        Self::connected(Transport::Tcp(addr.to_string()))
+    }
+
+    /// set the timeout of connect and write,default is 5s
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        if let Some(conn) = &self.conn {
+            let _ = conn.set_write_timeout(Some(timeout).filter(|v| !v.is_zero()));
+        }
+        self
+    }
+
+    /// set the backoff of reconnect,it doubles from min to max after every failure,default is 100ms to 30s
+    pub fn backoff(mut self, min: Duration, max: Duration) -> Self {
+        self.min_backoff = min;
+        self.max_backoff = max.max(min);
+        self.backoff = min;
+        self
+    }
+
+    /// set the message format,default is `SyslogFormat::Rfc5424`
+    pub fn format(mut self, format: SyslogFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// set the facility,default is `Facility::User`
+    pub fn facility(mut self, facility: Facility) -> Self {
+        self.facility = facility;
+        self
+    }
+
+    /// set the hostname,default is the hostname of system
+    pub fn hostname(mut self, hostname: &str) -> Self {
+        self.hostname = hostname.to_string();
+        self
+    }
+
+    /// set the app-name(the TAG of RFC 3164),default is the name of executable
+    pub fn app_name(mut self, app_name: &str) -> Self {
+        self.app_name = app_name.to_string();
+        self
+    }
+
+    /// set the procid,default is the process id
+    pub fn procid(mut self, procid: &str) -> Self {
+        self.procid = procid.to_string();
+        self
+    }
+
+    /// set the SD-ID of key-values,default is `fields@32473`
+    pub fn sd_id(mut self, sd_id: &str) -> Self {
+        self.sd_id = sd_id.to_string();
+        self
+    }
+
+    /// format a record into the buffer
+    fn format_record(&mut self, record: &FastLogRecord) {
+        self.buf.clear();
+        let pri = self.facility as u8 * 8 + severity(record.level);
+        match self.format {
+            SyslogFormat::Rfc5424 => {
+                let now = fastdate::DateTime::from(record.now);
+                let _ = write!(
+                    self.buf,
+                    "<{}>1 {:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:06}Z ",
+                    pri,
+                    now.year(),
+                    now.mon(),
+                    now.day(),
+                    now.hour(),
+                    now.minute(),
+                    now.sec(),
+                    now.nano() / 1000
+                );
+                write_header_field(&mut self.buf, &self.hostname, 255);
+                write_header_field(&mut self.buf, &self.app_name, 48);
+                write_header_field(&mut self.buf, &self.procid, 128);
+                self.buf.push_str("- ");
+                if record.fields.is_empty() {
+                    self.buf.push('-');
+                } else {
+                    self.buf.push('[');
+                    write_sd_name(&mut self.buf, &self.sd_id);
+                    for (k, v) in &record.fields {
+                        self.buf.push(' ');
+                        write_sd_name(&mut self.buf, k);
+                        self.buf.push_str("=\"");
+                        match v {
+                            FieldValue::Str(v) => write_sd_value(&mut self.buf, v),
+                            _ => {
+                                let _ = write!(self.buf, "{}", v);
+                            }
+                        }
+                        self.buf.push('"');
+                    }
+                    self.buf.push(']');
+                }
+                if !record.args.is_empty() {
+                    self.buf.push(' ');
+                    self.buf.push_str(&record.args);
+                }
+            }
+            SyslogFormat::Rfc3164 => {
+                const MONTHS: [&str; 12] = [
+                    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov",
+                    "Dec",
+                ];
+                let now = fastdate::DateTime::from(record.now).set_offset(fastdate::offset_sec());
+                let _ = write!(
+                    self.buf,
+                    "<{}>{} {:>2} {:02}:{:02}:{:02} {} {}[{}]: {}",
+                    pri,
+                    MONTHS[(now.mon() as usize).clamp(1, 12) - 1],
+                    now.day(),
+                    now.hour(),
+                    now.minute(),
+                    now.sec(),
+                    self.hostname,
+                    self.app_name,
+                    self.procid,
+                    record.args
+                );
+                for (k, v) in &record.fields {
+                    self.buf.push(' ');
+                    write_kv(&mut self.buf, k, v);
+                }
+            }
+        }
+    }
+
+    fn open(&self) -> std::io::Result<Conn> {
+        let timeout = Some(self.timeout).filter(|v| !v.is_zero());
+        let conn = match &self.transport {
+            #[cfg(unix)]
+            Transport::Unix(path) => {
+                let socket = UnixDatagram::unbound()?;
+                socket.connect(path)?;
+                Conn::Unix(socket)
+            }
+            Transport::Udp(addr) => {
+                let socket = UdpSocket::bind("0.0.0.0:0")?;
+                socket.connect(addr)?;
+                Conn::Udp(socket)
+            }
+            Transport::Tcp(addr) => {
+                let mut last = None;
+                let mut stream = None;
+                for addr in addr.to_socket_addrs()? {
+                    let result = match timeout {
+                        Some(timeout) => TcpStream::connect_timeout(&addr, timeout),
+                        None => TcpStream::connect(addr),
+                    };
+                    match result {
+                        Ok(v) => {
+                            stream = Some(v);
+                            break;
+                        }
+                        Err(e) => last = Some(e),
+                    }
+                }
+                match stream {
+                    Some(v) => Conn::Tcp(BufWriter::new(v)),
+                    None => return Err(last.unwrap_or_else(|| ErrorKind::AddrNotAvailable.into())),
+                }
+            }
+        };
+        conn.set_write_timeout(timeout)?;
+        Ok(conn)
+    }
+
+    /// connect when it is disconnected and the backoff is passed
+    fn connect(&mut self) -> bool {
+        if self.conn.is_some() {
+            return true;
+        }
+        if self.next_connect.is_some_and(|v| Instant::now() < v) {
+            return false;
+        }
+        match self.open() {
+            Ok(conn) => {
+                self.conn = Some(conn);
+                self.backoff = self.min_backoff;
+                self.next_connect = None;
+                true
+            }
+            Err(e) => {
+                self.disconnect(e);
+                false
+            }
+        }
+    }
+
+    /// drop the connection and wait for the backoff
+    fn disconnect(&mut self, e: std::io::Error) {
+        self.conn = None;
+        self.next_connect = Some(Instant::now() + self.backoff);
+        self.backoff = (self.backoff * 2).min(self.max_backoff);
+        self.error = Some(LogError::from(format!(
+            "syslog {:?}: {}",
+            self.transport, e
+        )));
+    }
+
+    /// send the buffer as a message
+    fn send(&mut self) -> std::io::Result<()> {
+        match &mut self.conn {
+            #[cfg(unix)]
+            Some(Conn::Unix(socket)) => socket.send(self.buf.as_bytes()).map(|_| ()),
+            Some(Conn::Udp(socket)) => socket.send(self.buf.as_bytes()).map(|_| ()),
+            Some(Conn::Tcp(v)) => {
+                write!(v, "{} ", self.buf.len()).and_then(|_| v.write_all(self.buf.as_bytes()))
+            }
+            None => Err(ErrorKind::NotConnected.into()),
+        }
+    }
+
+    /// a tcp stream is broken by any write error,a datagram socket only when the peer is gone
+    fn is_broken(&self, e: &std::io::Error) -> bool {
+        matches!(self.conn, Some(Conn::Tcp(_))) || is_disconnected(e)
+    }
+
+    /// send the buffer,a broken connection is opened again once without backoff
+    fn send_or_reopen(&mut self) -> std::io::Result<()> {
+        match self.send() {
+            Err(e) if self.is_broken(&e) => {
+                self.conn = None;
+                self.conn = Some(self.open()?);
+                self.send()
+            }
+            result => result,
+        }
+    }
+}
+
+impl LogAppender for SyslogAppender {
+    fn do_logs(&mut self, records: &[FastLogRecord]) {
+        let mut dropped = 0;
+        for x in records {
+            if x.command != Command::CommandRecord {
+                continue;
+            }
+            if !self.connect() {
+                dropped += 1;
+                continue;
+            }
+            self.format_record(x);
+            match self.send_or_reopen() {
+                Ok(()) => {}
+                Err(e) if self.conn.is_none() || self.is_broken(&e) => {
+                    self.disconnect(e);
+                    dropped += 1;
+                }
+                Err(e) => {
+                    self.error = Some(LogError::from(format!(
+                        "syslog {:?}: a record of {} bytes is dropped: {}",
+                        self.transport,
+                        self.buf.len(),
+                        e
+                    )));
+                }
+            }
+        }
+        if let Some(Conn::Tcp(v)) = &mut self.conn {
+            if let Err(e) = v.flush() {
+                self.disconnect(e);
+            }
+        }
+        if dropped > 0 && self.error.is_none() {
+            self.error = Some(LogError::from(format!(
+                "syslog {:?}: disconnected,{} records are dropped",
+                self.transport, dropped
+            )));
+        }
+    }
+
+    fn take_error(&mut self) -> Option<LogError> {
+        self.error.take()
+    }
+}
+
+/// write a header field of RFC 5424,it is printable ascii without space,`-` when it is empty
+fn write_header_field(out: &mut String, v: &str, max: usize) {
+    let start = out.len();
+    for c in v.chars().filter(|c| c.is_ascii_graphic()).take(max) {
+        out.push(c);
+    }
+    if out.len() == start {
+        out.push('-');
+    }
+    out.push(' ');
+}
+
+/// write a SD-NAME,it is printable ascii without `=`,space,`]` and `"`,at most 32 chars
+fn write_sd_name(out: &mut String, v: &str) {
+    let start = out.len();
+    for c in v
+        .chars()
+        .filter(|c| c.is_ascii_graphic() && !matches!(c, '=' | ']' | '"'))
+        .take(32)
+    {
+        out.push(c);
+    }
+    if out.len() == start {
+        out.push('_');
+    }
+}
+
+/// write a PARAM-VALUE,`"`,`\` and `]` are escaped
+fn write_sd_value(out: &mut String, v: &str) {
+    for c in v.chars() {
+        if matches!(c, '"' | '\\' | ']') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+}
@@ -0,0 +1,68 @@
+use crate::appender::FastLogRecord;
+use crate::{chan, Receiver, Sender};
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+/// the max buffers kept by the pool
+const POOL_LEN: usize = 1024;
+
+/// the capacity of a new buffer,most messages fit it without grow
+const MIN_BUFFER_CAPACITY: usize = 128;
+
+/// a bigger buffer is dropped instead of recycled
+const MAX_BUFFER_CAPACITY: usize = 1024;
+
+static POOL: OnceLock<BufferPool> = OnceLock::new();
+
+/// get the global BufferPool
+pub fn buffer_pool() -> &'static BufferPool {
+    POOL.get_or_init(|| BufferPool::new(POOL_LEN))
+}
+
+/// a pool of String buffers.
+/// the appender threads recycle the buffers of written records,and `Logger::log` reuse them
+/// so capture a record not allocate once the pool is warm
+pub struct BufferPool {
+    send: Sender<String>,
+    recv: Receiver<String>,
+}
+
+impl BufferPool {
+    pub fn new(len: usize) -> Self {
+        let (send, recv) = chan(Some(len));
+        Self { send, recv }
+    }
+
+    /// take an empty buffer,or a new one if the pool is empty
+    pub fn take(&self) -> String {
+        self.recv
+            .try_recv()
+            .unwrap_or_else(|_| String::with_capacity(MIN_BUFFER_CAPACITY))
+    }
+
+    /// put back the buffer,it is dropped if the pool is full or the buffer is too big
+    pub fn put(&self, mut buf: String) {
+        if buf.capacity() == 0 || buf.capacity() > MAX_BUFFER_CAPACITY {
+            return;
+        }
+        buf.clear();
+        let _ = self.send.try_send(buf);
+    }
+
+    /// put back the buffers of the record
+    pub fn recycle(&self, record: FastLogRecord) {
+        if let Cow::Owned(args) = record.args {
+            self.put(args);
+        }
+        self.put(record.formated);
+    }
+
+    /// the buffers in the pool
+    pub fn len(&self) -> usize {
+        self.recv.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.recv.is_empty()
+    }
+}
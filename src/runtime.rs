@@ -20,9 +20,10 @@ pub fn chan<T>(len: Option<usize>) -> (Sender<T>, Receiver<T>) {
 }
 
 #[cfg(feature = "runtime_thread")]
-pub fn spawn<F>(f: F) -> JoinHandle<()>
+pub fn spawn<F, T>(f: F) -> JoinHandle<T>
 where
-    F: FnOnce() + Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
 {
     std::thread::spawn(f)
 }
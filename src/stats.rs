@@ -0,0 +1,99 @@
+use crate::error::LogError;
+use std::fmt::{Display, Formatter};
+
+/// a snapshot of the logger,see `fastlog::stats`.
+/// the counters are counted since the process start
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogStats {
+    /// records waiting in the main channel
+    pub queue_len: usize,
+    /// the main channel capacity,None is unbounded
+    pub queue_cap: Option<usize>,
+    /// records sent to the main channel
+    pub accepted: u64,
+    /// records rejected by `Config::filters`
+    pub filtered: u64,
+    /// records dropped by `Config::overflow`
+    pub dropped: u64,
+    /// the format thread is running
+    pub running: bool,
+    pub appenders: Vec<AppenderStats>,
+}
+
+impl LogStats {
+    /// the logger is running and every appender thread is alive and healthy
+    pub fn is_healthy(&self) -> bool {
+        self.running && self.appenders.iter().all(|x| x.alive && x.healthy)
+    }
+}
+
+/// the stats of an appender thread,see `fastlog::appender_stats`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AppenderStats {
+    /// the appender name
+    pub name: String,
+    /// batches waiting in the appender channel
+    pub queue_len: usize,
+    /// the appender channel capacity(batches),None is unbounded
+    pub queue_cap: Option<usize>,
+    /// records dropped by the appender `OverflowPolicy`
+    pub dropped: u64,
+    /// batches written by `LogAppender::do_logs`
+    pub batches: u64,
+    /// formatted bytes written by `LogAppender::do_logs`
+    pub bytes: u64,
+    /// the last error of `LogAppender::take_error`
+    pub last_error: Option<String>,
+    /// the appender thread is not exit(or panic)
+    pub alive: bool,
+    /// the appender is not panicked,or it is restarted.
+    /// the records of an unhealthy appender are written by `AppenderConfig::fallback`
+    pub healthy: bool,
+    /// the panics of `LogAppender::do_logs`
+    pub panics: u64,
+}
+
+impl AppenderStats {
+    /// the appender channel is full,the appender can not keep up with the records
+    pub fn is_stalled(&self) -> bool {
+        match self.queue_cap {
+            Some(cap) => self.queue_len >= cap,
+            None => false,
+        }
+    }
+}
+
+/// an appender fault,reported to `Config::diagnostics`
+#[derive(Clone, Debug)]
+pub enum Diagnostic {
+    /// `LogAppender::do_logs` panicked,the appender is unhealthy until restarted
+    Panic { appender: String, message: String },
+    /// `LogAppender::take_error` return an error,or `AppenderConfig::restart` fail
+    Error { appender: String, error: LogError },
+    /// the panicked appender is replaced by `AppenderConfig::restart`
+    Restarted { appender: String },
+    /// the replaced appender not write its records before the timeout of `fastlog::reconfigure`,its thread is left running
+    Undrained { appender: String },
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Diagnostic::Panic { appender, message } => {
+                write!(f, "appender {} panicked: {}", appender, message)
+            }
+            Diagnostic::Error { appender, error } => {
+                let LogError::E(error) = error;
+                write!(f, "appender {} error: {}", appender, error)
+            }
+            Diagnostic::Restarted { appender } => write!(f, "appender {} restarted", appender),
+            Diagnostic::Undrained { appender } => {
+                write!(
+                    f,
+                    "appender {} not drained before reconfigure timeout",
+                    appender
+                )
+            }
+        }
+    }
+}
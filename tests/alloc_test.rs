@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod test {
+    use fastlog::appender::{FastLogRecord, LogAppender};
+    use fastlog::Config;
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    /// count the allocations of the current thread
+    struct CountAlloc;
+
+    thread_local! {
+        static ALLOCS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountAlloc {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let _ = ALLOCS.try_with(|v| v.set(v.get() + 1));
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: CountAlloc = CountAlloc;
+
+    struct A {}
+
+    impl LogAppender for A {
+        fn do_logs(&mut self, _records: &[FastLogRecord]) {}
+    }
+
+    #[test]
+    fn test_log_not_allocate() {
+        fastlog::init(Config::new().custom(A {}).chan_len(Some(1000))).unwrap();
+        //warm the buffer pool
+        for i in 0..500 {
+            log::info!("warm {}", i);
+        }
+        fastlog::flush().unwrap().wait();
+        assert!(!fastlog::pool::buffer_pool().is_empty());
+
+        let before = ALLOCS.with(|v| v.get());
+        for i in 0..100 {
+            log::info!("static");
+            log::info!("formatted {}", i);
+        }
+        assert_eq!(ALLOCS.with(|v| v.get()) - before, 0);
+    }
+}
@@ -0,0 +1,33 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::Collect;
+    use fastlog::{AppenderConfig, Config, FastLogFormatJson};
+    use log::LevelFilter;
+
+    #[test]
+    fn test_appender_level_format() {
+        let console = Collect::new();
+        let file = Collect::new();
+        fastlog::init(
+            Config::new()
+                .level(LevelFilter::Debug)
+                .append(AppenderConfig::new(console.clone()).level(LevelFilter::Info))
+                .append(AppenderConfig::new(file.clone()).format(FastLogFormatJson::new())),
+        )
+        .unwrap();
+        assert_eq!(log::max_level(), LevelFilter::Debug);
+        log::trace!("trace");
+        log::debug!("debug");
+        log::info!("info");
+        log::logger().flush();
+        let console = console.lines();
+        assert_eq!(console.len(), 1);
+        assert!(console[0].ends_with(" info\n"));
+        let file = file.lines();
+        assert_eq!(file.len(), 2);
+        assert!(file[0].starts_with("{\"args\":\"debug\""));
+        assert!(file[1].starts_with("{\"args\":\"info\""));
+    }
+}
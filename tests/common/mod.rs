@@ -0,0 +1,116 @@
+//! the helpers shared by the tests,`mod common;` in a test file
+#![allow(dead_code)]
+
+use fastlog::appender::{Command, FastLogRecord, LogAppender};
+use log::Level;
+use std::sync::{Arc, Mutex};
+use std::thread::sleep;
+use std::time::{Duration, SystemTime};
+
+/// the time of `stamped` records,2023-11-14T22:13:20.123456789Z
+pub const TIME: Duration = Duration::new(1_700_000_000, 123_456_789);
+
+/// a record,the line is both the args and the formatted line
+pub fn record(level: Level, target: &str, line: &str) -> FastLogRecord {
+    let mut record = FastLogRecord::new_command(Command::CommandRecord);
+    record.level = level;
+    record.target = target.to_string().into();
+    record.args = line.to_string().into();
+    record.formated = line.to_string();
+    record
+}
+
+/// a record at `TIME`
+pub fn stamped(level: Level, target: &str, line: &str) -> FastLogRecord {
+    let mut record = record(level, target, line);
+    record.now = SystemTime::UNIX_EPOCH + TIME;
+    record
+}
+
+/// an appender that keeps the batches it writes,a command is kept as None so a flush is not held
+#[derive(Clone, Default)]
+pub struct Collect {
+    batches: Arc<Mutex<Vec<Vec<Option<FastLogRecord>>>>>,
+    sleep: Duration,
+}
+
+impl Collect {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// sleep in every batch,a slow appender
+    pub fn sleep(mut self, sleep: Duration) -> Self {
+        self.sleep = sleep;
+        self
+    }
+
+    /// the records written
+    pub fn records(&self) -> Vec<FastLogRecord> {
+        let batches = self.batches.lock().unwrap();
+        batches.iter().flatten().flatten().cloned().collect()
+    }
+
+    /// the formatted lines written
+    pub fn lines(&self) -> Vec<String> {
+        self.records().into_iter().map(|x| x.formated).collect()
+    }
+
+    /// the args written
+    pub fn args(&self) -> Vec<String> {
+        self.records()
+            .into_iter()
+            .map(|x| x.args.to_string())
+            .collect()
+    }
+
+    /// the count of records written
+    pub fn len(&self) -> usize {
+        let batches = self.batches.lock().unwrap();
+        batches.iter().flatten().flatten().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// take the batches written,a command is `<command>`
+    pub fn take_batches(&self) -> Vec<Vec<String>> {
+        std::mem::take(&mut *self.batches.lock().unwrap())
+            .into_iter()
+            .map(|batch| {
+                batch
+                    .into_iter()
+                    .map(|x| match x {
+                        Some(x) => x.formated,
+                        None => "<command>".to_string(),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// forget the batches written
+    pub fn clear(&self) {
+        self.batches.lock().unwrap().clear();
+    }
+
+    /// is the appender dropped,the other clones of the handle must be dropped before
+    pub fn is_dropped(&self) -> bool {
+        Arc::strong_count(&self.batches) == 1
+    }
+}
+
+impl LogAppender for Collect {
+    fn do_logs(&mut self, records: &[FastLogRecord]) {
+        sleep(self.sleep);
+        let batch = records
+            .iter()
+            .map(|x| match x.command {
+                Command::CommandRecord => Some(x.clone()),
+                _ => None,
+            })
+            .collect();
+        self.batches.lock().unwrap().push(batch);
+    }
+}
@@ -0,0 +1,123 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::record;
+    use fastlog::appender::{FastLogRecord, RecordFormat};
+    use fastlog::plugin::console::{paint_level, ColorChoice, ConsoleAppender};
+    use fastlog::{AppenderConfig, Config, FastLogFormat, LogfmtFormat, PatternFormat};
+    use log::{Level, LevelFilter};
+    use std::ffi::OsString;
+    use std::io::{BufRead, BufReader};
+    use std::process::{Command, Stdio};
+    use std::time::{Duration, Instant};
+
+    fn painted(format: &dyn RecordFormat, mut record: FastLogRecord) -> String {
+        record.formated.clear();
+        format.do_format(&mut record);
+        let mut out = String::new();
+        paint_level(&mut out, &record);
+        out
+    }
+
+    #[test]
+    fn test_paint_level() {
+        //the level is colored where the format wrote it,not a target or thread with the level name
+        let format = PatternFormat::new("%T %l %m%n").unwrap();
+        let out = painted(&format, record(Level::Info, "info-worker", "info"));
+        assert_eq!(out, "info-worker \x1b[32mINFO\x1b[0m info\n");
+        let format = PatternFormat::new("%-6l|%m%n").unwrap();
+        let out = painted(&format, record(Level::Warn, "app", "WARN"));
+        assert_eq!(out, "\x1b[33mWARN\x1b[0m  |WARN\n");
+        let out = painted(&LogfmtFormat::new(), record(Level::Error, "app", "error"));
+        assert!(out.contains(" level=\x1b[31merror\x1b[0m msg=error"));
+        let out = painted(&FastLogFormat::new(), record(Level::Debug, "app", "x"));
+        assert!(out.contains(" \x1b[34mDEBUG\x1b[0m x\n"));
+        //a truncated level name,or a line without level,is written as it is
+        let format = PatternFormat::new("%.2l %m%n").unwrap();
+        assert_eq!(
+            painted(&format, record(Level::Info, "app", "INFO")),
+            "FO INFO\n"
+        );
+        let mut out = String::new();
+        paint_level(&mut out, &record(Level::Info, "", "print INFO\n"));
+        assert_eq!(out, "print INFO\n");
+    }
+
+    #[test]
+    fn test_color_choice() {
+        let env = |vars: &'static [(&str, &str)]| {
+            move |key: &str| {
+                vars.iter()
+                    .find(|(k, _)| *k == key)
+                    .map(|(_, v)| OsString::from(v))
+            }
+        };
+        assert!(ColorChoice::Always.enabled_by(false, env(&[])));
+        assert!(!ColorChoice::Never.enabled_by(true, env(&[])));
+        assert!(ColorChoice::Auto.enabled_by(true, env(&[])));
+        assert!(!ColorChoice::Auto.enabled_by(false, env(&[])));
+        assert!(ColorChoice::Auto.enabled_by(false, env(&[("CLICOLOR_FORCE", "1")])));
+        assert!(
+            !ColorChoice::Auto.enabled_by(true, env(&[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")]))
+        );
+        //an empty NO_COLOR is ignored
+        assert!(ColorChoice::Auto.enabled_by(true, env(&[("NO_COLOR", "")])));
+        assert!(
+            !ColorChoice::Auto.enabled_by(true, env(&[("CLICOLOR_FORCE", "0"), ("TERM", "dumb")]))
+        );
+    }
+
+    /// the test runs itself in a child process,the parent closes the stdout pipe while the child is logging
+    #[test]
+    fn test_broken_pipe() {
+        if std::env::var_os("FASTLOG_CONSOLE_CHILD").is_none() {
+            let mut child = Command::new(std::env::current_exe().unwrap())
+                .args(["test::test_broken_pipe", "--exact", "--nocapture"])
+                .env("FASTLOG_CONSOLE_CHILD", "1")
+                .stdout(Stdio::piped())
+                .stderr(Stdio::piped())
+                .spawn()
+                .unwrap();
+            let mut stdout = BufReader::new(child.stdout.take().unwrap());
+            let mut line = String::new();
+            while !line.contains("ready") {
+                line.clear();
+                assert!(stdout.read_line(&mut line).unwrap() > 0);
+            }
+            drop(stdout);
+            let out = child.wait_with_output().unwrap();
+            let stderr = String::from_utf8_lossy(&out.stderr);
+            assert!(out.status.success(), "{}", stderr);
+            assert!(stderr.contains("before broken pipe"));
+            assert!(stderr.contains("after broken pipe"));
+            assert!(!stderr.contains("ready"));
+            return;
+        }
+        fastlog::init(
+            Config::new().append(AppenderConfig::new(
+                ConsoleAppender::new()
+                    .color(ColorChoice::Never)
+                    .stderr(LevelFilter::Warn),
+            )),
+        )
+        .unwrap();
+        log::warn!("before broken pipe");
+        log::info!("ready");
+        fastlog::flush().unwrap().wait();
+        let start = Instant::now();
+        while fastlog::appender_stats()[0].last_error.is_none() {
+            assert!(start.elapsed() < Duration::from_secs(10));
+            log::info!("ready");
+            fastlog::flush().unwrap().wait();
+        }
+        log::info!("after broken pipe");
+        log::error!("after broken pipe");
+        fastlog::flush().unwrap().wait();
+        let stats = &fastlog::appender_stats()[0];
+        assert!(stats.last_error.as_ref().unwrap().contains("stdout"));
+        assert!(stats.alive && stats.panics == 0);
+        //the test harness can not print the result to a closed stdout
+        std::process::exit(0);
+    }
+}
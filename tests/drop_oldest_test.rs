@@ -0,0 +1,62 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::Collect;
+    use fastlog::{AppenderConfig, Config, OverflowPolicy};
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    /// the number logged by the `from` thread,None for the other lines
+    fn number(line: &str, from: &str) -> Option<usize> {
+        line.trim_end()
+            .rsplit(' ')
+            .next()?
+            .strip_prefix(from)?
+            .parse()
+            .ok()
+    }
+
+    #[test]
+    fn test_drop_oldest_keep_commands() {
+        let records = Collect::new().sleep(Duration::from_millis(50));
+        fastlog::init(
+            Config::new()
+                .chan_len(Some(2))
+                .overflow(OverflowPolicy::DropOldest)
+                .append(
+                    AppenderConfig::new(records.clone())
+                        .chan_len(1)
+                        .overflow(OverflowPolicy::Block),
+                ),
+        )
+        .unwrap();
+        //another producer evicts records at the same time
+        let other = std::thread::spawn(|| {
+            for i in 0..200 {
+                log::info!("b{}", i);
+                sleep(Duration::from_micros(100));
+            }
+        });
+        for i in 0..10 {
+            log::info!("a{}", i);
+            sleep(Duration::from_millis(1));
+        }
+        //the flush command is taken from the full channel by the next records,but not dropped or moved
+        let wg = fastlog::flush().unwrap();
+        for i in 10..30 {
+            log::info!("a{}", i);
+        }
+        wg.wait();
+        other.join().unwrap();
+        fastlog::shutdown(None).unwrap();
+        let lines: Vec<String> = records.take_batches().into_iter().flatten().collect();
+        let flush = lines.iter().position(|x| x == "<command>").unwrap();
+        for (i, line) in lines.iter().enumerate() {
+            if let Some(n) = number(line, "a") {
+                assert_eq!(n < 10, i < flush, "{:?}", lines);
+            }
+        }
+        assert!(fastlog::stats().dropped > 0);
+    }
+}
@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod test {
+    use fastlog::appender::{Command, FastLogRecord, LogAppender};
+    use fastlog::error::LogError;
+    use fastlog::stats::Diagnostic;
+    use fastlog::{AppenderConfig, Config};
+    use parking_lot::Mutex;
+    use std::sync::Arc;
+
+    type Lines = Arc<Mutex<Vec<String>>>;
+
+    /// panic on the record "boom"
+    struct Panicky {
+        lines: Lines,
+    }
+
+    impl LogAppender for Panicky {
+        fn do_logs(&mut self, records: &[FastLogRecord]) {
+            for x in records {
+                if x.args == "boom" {
+                    panic!("boom");
+                }
+                if x.command == Command::CommandRecord {
+                    self.lines.lock().push(x.args.to_string());
+                }
+            }
+        }
+    }
+
+    fn lines() -> Lines {
+        Arc::new(Mutex::new(vec![]))
+    }
+
+    #[test]
+    fn test_appender_fault() {
+        let (restarted, fallback) = (lines(), lines());
+        let (broken, broken_fallback) = (lines(), lines());
+        let diagnostics = lines();
+        let restarted_lines = restarted.clone();
+        let diagnostics_lines = diagnostics.clone();
+        fastlog::init(
+            Config::new()
+                .diagnostics(move |x| {
+                    let kind = match x {
+                        Diagnostic::Panic { .. } => "panic",
+                        Diagnostic::Error { .. } => "error",
+                        Diagnostic::Restarted { .. } => "restarted",
+                        Diagnostic::Undrained { .. } => "undrained",
+                    };
+                    diagnostics_lines.lock().push(kind.to_string());
+                })
+                .append(
+                    AppenderConfig::new(Panicky { lines: lines() })
+                        .name("restart")
+                        .restart(move || {
+                            Ok::<_, LogError>(Panicky {
+                                lines: restarted_lines.clone(),
+                            })
+                        })
+                        .fallback(Panicky {
+                            lines: fallback.clone(),
+                        }),
+                )
+                .append(
+                    AppenderConfig::new(Panicky {
+                        lines: broken.clone(),
+                    })
+                    .name("broken")
+                    .fallback(Panicky {
+                        lines: broken_fallback.clone(),
+                    }),
+                ),
+        )
+        .unwrap();
+        log::info!("before");
+        fastlog::flush().unwrap().wait();
+        log::info!("boom");
+        fastlog::flush().unwrap().wait();
+        log::info!("after");
+        fastlog::flush().unwrap().wait();
+
+        assert_eq!(*restarted.lock(), vec!["after".to_string()]);
+        assert_eq!(*broken.lock(), vec!["before".to_string()]);
+        assert_eq!(*broken_fallback.lock(), vec!["after".to_string()]);
+        let diagnostics = diagnostics.lock().clone();
+        assert_eq!(diagnostics.iter().filter(|x| *x == "panic").count(), 2);
+        assert_eq!(diagnostics.iter().filter(|x| *x == "restarted").count(), 1);
+        let stats = fastlog::stats();
+        assert!(!stats.is_healthy());
+        assert!(stats.appenders[0].healthy);
+        assert!(!stats.appenders[1].healthy);
+        assert!(stats.appenders.iter().all(|x| x.alive && x.panics == 1));
+        assert!(fastlog::shutdown(None).unwrap().is_drained());
+    }
+}
@@ -0,0 +1,226 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::record;
+    use fastlog::appender::{Command, FastLogRecord, FieldValue, LogAppender};
+    use fastlog::plugin::file_dynamic::DynamicFileAppender;
+    use log::Level;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    /// a path and a batch written to it,an empty batch is the open of the path
+    type Batch = (String, Vec<String>);
+
+    #[derive(Clone, Default)]
+    struct Opened(Arc<Mutex<Vec<Batch>>>);
+
+    struct Child {
+        path: String,
+        opened: Opened,
+    }
+
+    impl LogAppender for Child {
+        fn do_logs(&mut self, records: &[FastLogRecord]) {
+            let batch = records
+                .iter()
+                .map(|x| match x.command {
+                    Command::CommandRecord => x.formated.clone(),
+                    _ => "<command>".to_string(),
+                })
+                .collect();
+            self.opened
+                .0
+                .lock()
+                .unwrap()
+                .push((self.path.clone(), batch));
+        }
+    }
+
+    fn appender(template: &str, opened: &Opened) -> DynamicFileAppender {
+        let opened = opened.clone();
+        DynamicFileAppender::with_factory(template, move |path| {
+            opened.0.lock().unwrap().push((path.to_string(), vec![]));
+            Ok(Child {
+                path: path.to_string(),
+                opened: opened.clone(),
+            })
+        })
+        .unwrap()
+    }
+
+    /// a record with the tenant key-value
+    fn keyed(target: &str, tenant: Option<&str>, line: &str) -> FastLogRecord {
+        let mut record = record(Level::Info, target, line);
+        if let Some(tenant) = tenant {
+            record
+                .fields
+                .push(("tenant".to_string(), FieldValue::Str(tenant.to_string())));
+        }
+        record
+    }
+
+    #[test]
+    fn test_template() {
+        let opened = Opened::default();
+        let mut files = appender("logs/{level}/tenant-{kv.tenant}/{target}.log", &opened);
+        files.do_logs(&[
+            keyed("app", Some("a"), "1"),
+            keyed("app::db", Some("../etc"), "2"),
+            keyed("app", None, "3"),
+            keyed("app", Some("a"), "4"),
+        ]);
+        let opened = std::mem::take(&mut *opened.0.lock().unwrap());
+        let batch = |path: &str| {
+            opened
+                .iter()
+                .find(|(p, v)| p == path && !v.is_empty())
+                .map(|(_, v)| v.clone())
+        };
+        assert_eq!(
+            batch("logs/INFO/tenant-a/app.log"),
+            Some(vec!["1".to_string(), "4".to_string()])
+        );
+        assert_eq!(
+            batch("logs/INFO/tenant-.._etc/app__db.log"),
+            Some(vec!["2".to_string()])
+        );
+        assert_eq!(
+            batch("logs/INFO/tenant-unknown/app.log"),
+            Some(vec!["3".to_string()])
+        );
+        assert_eq!(files.open_files(), 3);
+    }
+
+    #[test]
+    fn test_unknown_placeholder() {
+        assert!(DynamicFileAppender::new("logs/{thread}.log").is_err());
+        assert!(DynamicFileAppender::new("logs/{kv.}.log").is_err());
+        assert!(DynamicFileAppender::new("logs/{target.log").is_err());
+    }
+
+    #[test]
+    fn test_evict_in_batch() {
+        let opened = Opened::default();
+        let mut files = appender("{target}", &opened).max_open(2);
+        files.do_logs(&[
+            keyed("a", None, "1"),
+            keyed("b", None, "2"),
+            keyed("c", None, "3"),
+            keyed("a", None, "4"),
+        ]);
+        assert_eq!(files.open_files(), 2);
+        let mut written: Vec<String> = opened
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .flat_map(|(_, v)| v.clone())
+            .collect();
+        written.sort();
+        //the records of an evicted file are written before it is closed
+        assert_eq!(written, ["1", "2", "3", "4"]);
+    }
+
+    #[test]
+    fn test_lru() {
+        let opened = Opened::default();
+        let mut files = appender("{target}", &opened).max_open(2);
+        files.do_logs(&[keyed("a", None, "1")]);
+        std::thread::sleep(Duration::from_millis(2));
+        files.do_logs(&[keyed("b", None, "2")]);
+        std::thread::sleep(Duration::from_millis(2));
+        files.do_logs(&[keyed("a", None, "3")]);
+        //b is the least recently used
+        files.do_logs(&[keyed("c", None, "4")]);
+        files.do_logs(&[keyed("b", None, "5")]);
+        assert_eq!(files.open_files(), 2);
+        let opens: Vec<String> = opened
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, v)| v.is_empty())
+            .map(|(p, _)| p.clone())
+            .collect();
+        assert_eq!(opens, vec!["a", "b", "c", "b"]);
+    }
+
+    #[test]
+    fn test_idle_timeout() {
+        let opened = Opened::default();
+        let mut files = appender("{target}", &opened).idle_timeout(Duration::from_millis(50));
+        files.do_logs(&[keyed("a", None, "1"), keyed("b", None, "2")]);
+        assert_eq!(files.open_files(), 2);
+        std::thread::sleep(Duration::from_millis(100));
+        files.do_logs(&[keyed("b", None, "3")]);
+        assert_eq!(files.open_files(), 1);
+    }
+
+    #[test]
+    fn test_idle_tick() {
+        let opened = Opened::default();
+        let mut files = appender("{target}", &opened).idle_timeout(Duration::from_millis(50));
+        assert_eq!(files.next_tick(), None);
+        files.do_logs(&[keyed("a", None, "1"), keyed("b", None, "2")]);
+        assert_eq!(files.next_tick(), Some(Duration::from_millis(50)));
+        std::thread::sleep(Duration::from_millis(100));
+        files.tick();
+        assert_eq!(files.open_files(), 0);
+        assert_eq!(files.next_tick(), None);
+    }
+
+    #[test]
+    fn test_commands() {
+        let opened = Opened::default();
+        let mut files = appender("{target}", &opened);
+        files.do_logs(&[
+            keyed("a", None, "1"),
+            keyed("b", None, "2"),
+            FastLogRecord::new_command(Command::CommandExit),
+        ]);
+        let mut batches: Vec<Batch> = opened
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, v)| !v.is_empty())
+            .cloned()
+            .collect();
+        batches.sort();
+        assert_eq!(
+            batches,
+            vec![
+                (
+                    "a".to_string(),
+                    vec!["1".to_string(), "<command>".to_string()]
+                ),
+                (
+                    "b".to_string(),
+                    vec!["2".to_string(), "<command>".to_string()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_file() {
+        let dir = "target/test/file_dynamic/";
+        let _ = std::fs::remove_dir_all(dir);
+        let mut files =
+            DynamicFileAppender::new(&format!("{}tenant-{{kv.tenant}}.log", dir)).unwrap();
+        files.do_logs(&[
+            keyed("app", Some("a"), "a1\n"),
+            keyed("app", Some("b"), "b1\n"),
+        ]);
+        drop(files);
+        assert_eq!(
+            std::fs::read_to_string(format!("{}tenant-a.log", dir)).unwrap(),
+            "a1\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(format!("{}tenant-b.log", dir)).unwrap(),
+            "b1\n"
+        );
+    }
+}
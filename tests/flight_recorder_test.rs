@@ -0,0 +1,76 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::{record, Collect};
+    use fastlog::appender::{Command, FastLogRecord, LogAppender};
+    use fastlog::plugin::flight_recorder::FlightRecorderAppender;
+    use log::Level;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn test_trigger() {
+        let lines = Collect::new();
+        let mut appender = FlightRecorderAppender::new(lines.clone()).max_records(3);
+        appender.do_logs(&[
+            record(Level::Debug, "app", "1"),
+            record(Level::Trace, "app", "2"),
+            record(Level::Info, "app", "3"),
+            record(Level::Debug, "app", "4"),
+        ]);
+        assert!(lines.take_batches().concat().is_empty());
+        appender.do_logs(&[
+            record(Level::Error, "app", "e1"),
+            record(Level::Info, "app", "5"),
+        ]);
+        assert_eq!(lines.take_batches().concat(), ["2", "3", "4", "e1"]);
+        appender.do_logs(&[record(Level::Error, "app", "e2")]);
+        assert_eq!(lines.take_batches().concat(), ["5", "e2"]);
+        appender.do_logs(&[FastLogRecord::new_command(Command::CommandExit)]);
+        assert_eq!(lines.take_batches().concat(), ["<command>"]);
+    }
+
+    #[test]
+    fn test_limits() {
+        let lines = Collect::new();
+        let mut appender = FlightRecorderAppender::new(lines.clone())
+            .max_bytes(6)
+            .trigger(Level::Warn);
+        appender.do_logs(&[
+            record(Level::Info, "app", "aa"),
+            record(Level::Info, "app", "bb"),
+            record(Level::Info, "app", "cc"),
+            record(Level::Info, "app", "dd"),
+            record(Level::Warn, "app", "w"),
+        ]);
+        assert_eq!(lines.take_batches().concat(), ["bb", "cc", "dd", "w"]);
+
+        let mut appender =
+            FlightRecorderAppender::new(lines.clone()).max_age(Duration::from_secs(60));
+        let mut old = record(Level::Info, "app", "old");
+        old.now = SystemTime::now() - Duration::from_secs(120);
+        appender.do_logs(&[
+            old,
+            record(Level::Info, "app", "new"),
+            record(Level::Error, "app", "e"),
+        ]);
+        assert_eq!(lines.take_batches().concat(), ["new", "e"]);
+    }
+
+    #[test]
+    fn test_dump() {
+        let lines = Collect::new();
+        let mut appender = FlightRecorderAppender::new(lines.clone());
+        let recorder = appender.recorder();
+        appender.do_logs(&[
+            record(Level::Debug, "app", "a"),
+            record(Level::Debug, "app", "b"),
+        ]);
+        assert_eq!(recorder.len(), 2);
+        assert_eq!(recorder.dump(), 2);
+        assert_eq!(lines.take_batches().concat(), ["a", "b"]);
+        assert!(recorder.is_empty());
+        assert_eq!(recorder.dump(), 0);
+        assert!(lines.take_batches().concat().is_empty());
+    }
+}
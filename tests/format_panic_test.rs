@@ -0,0 +1,73 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::Collect;
+    use fastlog::appender::{FastLogRecord, RecordFormat};
+    use fastlog::stats::Diagnostic;
+    use fastlog::{AppenderConfig, Config, OverflowPolicy};
+    use std::sync::{Arc, Mutex};
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    /// panic on the record "boom"
+    struct Boom;
+
+    impl RecordFormat for Boom {
+        fn do_format(&self, arg: &mut FastLogRecord) {
+            if arg.args == "boom" {
+                panic!("boom");
+            }
+            arg.formated.push_str(&arg.args);
+        }
+    }
+
+    #[test]
+    fn test_format_worker_panic() {
+        let records = Collect::new().sleep(Duration::from_millis(200));
+        let panics = Arc::new(Mutex::new(vec![]));
+        let hook = panics.clone();
+        fastlog::init(
+            Config::new()
+                .format(Boom)
+                .format_workers(2)
+                .diagnostics(move |x| {
+                    if let Diagnostic::Panic { appender, .. } = x {
+                        hook.lock().unwrap().push(appender.clone());
+                    }
+                })
+                .append(
+                    AppenderConfig::new(records.clone())
+                        .chan_len(1)
+                        .overflow(OverflowPolicy::Block),
+                ),
+        )
+        .unwrap();
+        //the format thread is blocked by the slow appender,the next records are one batch
+        for i in 0..3 {
+            log::info!("{}", i);
+            sleep(Duration::from_millis(20));
+        }
+        for i in 3..1000 {
+            log::info!("{}", i);
+        }
+        //the last chunk is formatted by the worker
+        log::info!("boom");
+        fastlog::flush().unwrap().wait();
+        log::info!("after");
+        fastlog::shutdown(None).unwrap();
+        assert_eq!(*panics.lock().unwrap(), ["format worker"]);
+        let lines = records.lines();
+        assert_eq!(lines[..3], ["0", "1", "2"]);
+        assert!(lines.contains(&"after".to_string()));
+        assert!(!lines.contains(&"boom".to_string()));
+        let dropped = lines
+            .iter()
+            .find_map(|x| x.strip_suffix(" records dropped"))
+            .unwrap();
+        assert_eq!(
+            lines.len() - 1 + dropped.parse::<usize>().unwrap(),
+            1000 + 2
+        );
+    }
+}
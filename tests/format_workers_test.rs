@@ -0,0 +1,53 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::Collect;
+    use fastlog::{AppenderConfig, Config, FastLogFormatJson};
+    use std::time::Duration;
+
+    /// the threads of this process
+    #[cfg(target_os = "linux")]
+    fn threads() -> usize {
+        let status = std::fs::read_to_string("/proc/self/status").unwrap();
+        status
+            .lines()
+            .find_map(|v| v.strip_prefix("Threads:"))
+            .unwrap()
+            .trim()
+            .parse()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_format_workers_keep_order() {
+        #[cfg(target_os = "linux")]
+        let before = threads();
+        let text = Collect::new();
+        let json = Collect::new();
+        fastlog::init(
+            Config::new()
+                .format_workers(4)
+                .custom(text.clone())
+                .append(AppenderConfig::new(json.clone()).format(FastLogFormatJson::new())),
+        )
+        .unwrap();
+        for i in 0..10000 {
+            log::info!("{}", i);
+        }
+        fastlog::flush().unwrap().wait();
+        let text = text.lines();
+        let json = json.lines();
+        assert_eq!(text.len(), 10000);
+        assert_eq!(json.len(), 10000);
+        for i in 0..10000 {
+            assert!(text[i].ends_with(&format!(" {}\n", i)));
+            assert!(json[i].contains(&format!("\"args\":\"{}\"", i)));
+        }
+        //the format workers are joined too
+        let report = fastlog::shutdown(Some(Duration::from_secs(5))).unwrap();
+        assert!(report.is_drained());
+        #[cfg(target_os = "linux")]
+        assert_eq!(threads(), before);
+    }
+}
@@ -0,0 +1,201 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::stamped;
+    use fastlog::appender::{Command, FastLogRecord, FieldValue, LogAppender};
+    use fastlog::plugin::http::{HttpAppender, HttpBody};
+    use fastlog::{AppenderConfig, Config};
+    use log::Level;
+    use serde_json::Value;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc::{channel, Receiver};
+    use std::time::Duration;
+
+    struct Request {
+        head: String,
+        body: Vec<u8>,
+    }
+
+    /// a stand-in http server,it responds the statuses in order
+    fn server(statuses: Vec<u16>) -> (String, Receiver<Request>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        let (send, recv) = channel();
+        std::thread::spawn(move || {
+            for status in statuses {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(&stream);
+                let mut head = String::new();
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line == "\r\n" {
+                        break;
+                    }
+                    head.push_str(&line);
+                }
+                let len: usize = head
+                    .lines()
+                    .find_map(|v| v.strip_prefix("Content-Length: "))
+                    .unwrap()
+                    .parse()
+                    .unwrap();
+                let mut body = vec![0u8; len];
+                reader.read_exact(&mut body).unwrap();
+                (&stream)
+                    .write_all(
+                        format!("HTTP/1.1 {} X\r\nContent-Length: 0\r\n\r\n", status).as_bytes(),
+                    )
+                    .unwrap();
+                send.send(Request { head, body }).unwrap();
+            }
+        });
+        (addr, recv)
+    }
+
+    #[test]
+    fn test_ndjson() {
+        let (addr, recv) = server(vec![200]);
+        let mut appender = HttpAppender::new(&format!("http://{}/ingest", addr), HttpBody::Ndjson)
+            .unwrap()
+            .header("Authorization", "Bearer x");
+        let mut warn = stamped(Level::Warn, "app", "c");
+        warn.fields = vec![("user_id".to_string(), FieldValue::I64(42))];
+        appender.do_logs(&[stamped(Level::Info, "app", "a \"b\"\n"), warn]);
+        assert!(appender.take_error().is_none());
+        let request = recv.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(request.head.starts_with("POST /ingest HTTP/1.1\r\n"));
+        assert!(request
+            .head
+            .contains("Content-Type: application/x-ndjson\r\n"));
+        assert!(request.head.contains("Authorization: Bearer x\r\n"));
+        let body = String::from_utf8(request.body).unwrap();
+        let lines: Vec<Value> = body
+            .lines()
+            .map(|v| serde_json::from_str(v).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["args"], "a \"b\"\n");
+        assert_eq!(lines[1]["level"], "WARN");
+        assert_eq!(lines[1]["user_id"], 42);
+    }
+
+    #[test]
+    fn test_elastic_bulk() {
+        let (addr, recv) = server(vec![200]);
+        let mut appender = HttpAppender::new(
+            &format!("http://{}/_bulk", addr),
+            HttpBody::ElasticBulk("logs".to_string()),
+        )
+        .unwrap();
+        appender.do_logs(&[
+            stamped(Level::Info, "app", "a"),
+            stamped(Level::Info, "app", "b"),
+        ]);
+        let request = recv.recv_timeout(Duration::from_secs(5)).unwrap();
+        let body = String::from_utf8(request.body).unwrap();
+        assert!(body.ends_with('\n'));
+        let lines: Vec<Value> = body
+            .lines()
+            .map(|v| serde_json::from_str(v).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], serde_json::json!({"index": {"_index": "logs"}}));
+        assert_eq!(lines[1]["args"], "a");
+        assert_eq!(lines[2], lines[0]);
+        assert_eq!(lines[3]["args"], "b");
+    }
+
+    #[test]
+    fn test_loki_coalesce() {
+        let (addr, recv) = server(vec![200]);
+        let mut appender =
+            HttpAppender::new(&format!("http://{}/loki/api/v1/push", addr), HttpBody::Loki)
+                .unwrap()
+                .label("app", "demo")
+                .coalesce(1024 * 1024, Duration::from_secs(60));
+        appender.do_logs(&[
+            stamped(Level::Info, "app", "a"),
+            stamped(Level::Error, "db", "b"),
+        ]);
+        appender.do_logs(&[stamped(Level::Info, "app", "c")]);
+        assert!(recv.try_recv().is_err());
+        appender.do_logs(&[FastLogRecord::new_command(Command::CommandExit)]);
+        let request = recv.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(request.head.contains("Content-Type: application/json\r\n"));
+        let body: Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({"streams": [
+                {"stream": {"level": "info", "target": "app", "app": "demo"},
+                 "values": [["1700000000123456789", "a"], ["1700000000123456789", "c"]]},
+                {"stream": {"level": "error", "target": "db", "app": "demo"},
+                 "values": [["1700000000123456789", "b"]]}
+            ]})
+        );
+    }
+
+    #[test]
+    fn test_coalesce_tick() {
+        let (addr, recv) = server(vec![200]);
+        let appender = HttpAppender::new(&format!("http://{}", addr), HttpBody::Ndjson)
+            .unwrap()
+            .coalesce(1024 * 1024, Duration::from_millis(50));
+        fastlog::init(Config::new().append(AppenderConfig::new(appender))).unwrap();
+        //no other batch and no flush,the kept record is sent by the tick
+        log::info!("a");
+        let request = recv.recv_timeout(Duration::from_secs(5)).unwrap();
+        let line: Value = serde_json::from_slice(request.body.trim_ascii_end()).unwrap();
+        assert_eq!(line["args"], "a");
+        fastlog::exit().unwrap();
+    }
+
+    #[test]
+    fn test_retry() {
+        let (addr, recv) = server(vec![503, 429, 200, 400]);
+        let mut appender = HttpAppender::new(&format!("http://{}", addr), HttpBody::Ndjson)
+            .unwrap()
+            .retry(3, Duration::from_millis(1), Duration::from_millis(5));
+        appender.do_logs(&[stamped(Level::Info, "app", "a")]);
+        assert!(appender.take_error().is_none());
+        for _ in 0..3 {
+            let request = recv.recv_timeout(Duration::from_secs(5)).unwrap();
+            assert!(request.head.starts_with("POST / HTTP/1.1\r\n"));
+        }
+        //a client error is not retried
+        appender.do_logs(&[stamped(Level::Info, "app", "b")]);
+        recv.recv_timeout(Duration::from_secs(5)).unwrap();
+        let error = appender.take_error().unwrap().to_string();
+        assert!(
+            error.contains("status 400,1 records are dropped"),
+            "{}",
+            error
+        );
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_gzip() {
+        let (addr, recv) = server(vec![200]);
+        let mut appender = HttpAppender::new(&format!("http://{}", addr), HttpBody::Ndjson)
+            .unwrap()
+            .gzip(true);
+        appender.do_logs(&[stamped(Level::Info, "app", "a")]);
+        let request = recv.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(request.head.contains("Content-Encoding: gzip\r\n"));
+        let mut body = String::new();
+        flate2::read::GzDecoder::new(&request.body[..])
+            .read_to_string(&mut body)
+            .unwrap();
+        let line: Value = serde_json::from_str(body.trim_end()).unwrap();
+        assert_eq!(line["args"], "a");
+    }
+
+    #[test]
+    fn test_url() {
+        assert!(HttpAppender::new("https://127.0.0.1/", HttpBody::Ndjson).is_err());
+        assert!(HttpAppender::new("http:///", HttpBody::Ndjson).is_err());
+    }
+}
@@ -0,0 +1,80 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::Collect;
+    use fastlog::appender::{FastLogRecord, LogAppender};
+    use fastlog::{AppenderConfig, Config, OverflowPolicy};
+    use std::sync::mpsc::Receiver;
+    use std::thread::sleep;
+    use std::time::{Duration, Instant};
+
+    /// stall until a message is received
+    struct Stuck {
+        release: Receiver<()>,
+    }
+
+    impl LogAppender for Stuck {
+        fn do_logs(&mut self, _records: &[FastLogRecord]) {
+            let _ = self.release.recv();
+        }
+    }
+
+    fn run(overflow: OverflowPolicy, level: log::Level) {
+        let fast = Collect::new();
+        let (release, r) = std::sync::mpsc::channel();
+        fastlog::init(
+            Config::new().custom(fast.clone()).append(
+                AppenderConfig::new(Stuck { release: r })
+                    .name("stuck")
+                    .chan_len(1)
+                    .overflow(overflow),
+            ),
+        )
+        .unwrap();
+        //the flush and exit commands of the stuck appender wait aside
+        let mut flushed = vec![];
+        for i in 0..100 {
+            log::log!(level, "{}", i);
+            if i % 10 == 0 {
+                flushed.push(fastlog::flush().unwrap());
+            }
+            sleep(Duration::from_millis(1));
+        }
+        //the fast appender is not blocked by the stuck one
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while fast.len() < 100 && Instant::now() < deadline {
+            sleep(Duration::from_millis(1));
+        }
+        assert_eq!(fast.len(), 100, "{:?}", overflow);
+        let stats = fastlog::appender_stats();
+        assert_eq!(stats.len(), 2);
+        assert!(!stats[0].is_stalled());
+        assert_eq!(stats[0].dropped, 0);
+        assert_eq!(stats[1].name, "stuck");
+        assert!(stats[1].is_stalled());
+        assert!(stats[1].dropped > 0);
+        //the flushes finish when the stuck appender is released
+        drop(release);
+        for x in flushed {
+            x.wait();
+        }
+        assert!(fastlog::shutdown(Some(Duration::from_secs(10)))
+            .unwrap()
+            .is_drained());
+        assert!(fastlog::appender_stats().is_empty());
+    }
+
+    #[test]
+    fn test_stalled_appender() {
+        run(OverflowPolicy::DropNewest, log::Level::Info);
+        run(OverflowPolicy::DropOldest, log::Level::Info);
+        run(
+            OverflowPolicy::BlockTimeout(Duration::from_millis(1)),
+            log::Level::Info,
+        );
+        run(OverflowPolicy::ShedByLevel, log::Level::Info);
+        //ERROR waits the stuck appender once,then it is dropped like the others
+        run(OverflowPolicy::ShedByLevel, log::Level::Error);
+    }
+}
@@ -0,0 +1,197 @@
+#[cfg(all(test, target_os = "linux"))]
+mod test {
+    use fastlog::appender::{Command, FastLogRecord, FieldValue, LogAppender};
+    use fastlog::plugin::journald::JournaldAppender;
+    use log::Level;
+    use rustix::net::{recvmsg, RecvAncillaryBuffer, RecvAncillaryMessage, RecvFlags};
+    use std::fs::File;
+    use std::io::{IoSliceMut, Read, Seek, SeekFrom};
+    use std::mem::MaybeUninit;
+    use std::os::unix::net::UnixDatagram;
+    use std::time::Duration;
+
+    fn server(name: &str) -> (UnixDatagram, String) {
+        let _ = std::fs::create_dir_all("target/test");
+        let path = format!("target/test/journald_{}_{}.sock", name, std::process::id());
+        let _ = std::fs::remove_file(&path);
+        (UnixDatagram::bind(&path).unwrap(), path)
+    }
+
+    fn record(args: &str) -> FastLogRecord {
+        let mut record = FastLogRecord::from(
+            &log::Record::builder()
+                .level(Level::Warn)
+                .target("app")
+                .module_path_static(Some("app::login"))
+                .file_static(Some("src/login.rs"))
+                .line(Some(12))
+                .args(format_args!("{}", args))
+                .build(),
+        );
+        record.fields = vec![
+            ("user_id".to_string(), FieldValue::I64(42)),
+            (
+                "_trusted.key".to_string(),
+                FieldValue::Str("a\nb".to_string()),
+            ),
+        ];
+        record
+    }
+
+    /// parse the native protocol
+    fn parse(mut data: &[u8]) -> Vec<(String, Vec<u8>)> {
+        let mut fields = vec![];
+        while !data.is_empty() {
+            let end = data.iter().position(|v| *v == b'\n').unwrap();
+            let line = &data[..end];
+            match line.iter().position(|v| *v == b'=') {
+                Some(eq) => {
+                    fields.push((
+                        String::from_utf8(line[..eq].to_vec()).unwrap(),
+                        line[eq + 1..].to_vec(),
+                    ));
+                    data = &data[end + 1..];
+                }
+                None => {
+                    let len = u64::from_le_bytes(data[end + 1..end + 9].try_into().unwrap());
+                    let value = &data[end + 9..end + 9 + len as usize];
+                    assert_eq!(data[end + 9 + len as usize], b'\n');
+                    fields.push((String::from_utf8(line.to_vec()).unwrap(), value.to_vec()));
+                    data = &data[end + 10 + len as usize..];
+                }
+            }
+        }
+        fields
+    }
+
+    fn field<'a>(fields: &'a [(String, Vec<u8>)], name: &str) -> &'a [u8] {
+        &fields.iter().find(|(k, _)| k == name).unwrap().1
+    }
+
+    #[test]
+    fn test_native_protocol() {
+        let (server, path) = server("native");
+        let mut appender = JournaldAppender::with_path(&path)
+            .unwrap()
+            .identifier("app");
+        appender.do_logs(&[
+            FastLogRecord::new_command(Command::CommandExit),
+            record("login"),
+        ]);
+        let mut buf = vec![0u8; 4096];
+        let n = server.recv(&mut buf).unwrap();
+        let fields = parse(&buf[..n]);
+        let names: Vec<&str> = fields.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "PRIORITY",
+                "MESSAGE",
+                "CODE_FILE",
+                "CODE_LINE",
+                "CODE_MODULE",
+                "TARGET",
+                "SYSLOG_IDENTIFIER",
+                "USER_ID",
+                "TRUSTED_KEY"
+            ]
+        );
+        assert_eq!(field(&fields, "PRIORITY"), b"4");
+        assert_eq!(field(&fields, "MESSAGE"), b"login");
+        assert_eq!(field(&fields, "CODE_LINE"), b"12");
+        assert_eq!(field(&fields, "CODE_MODULE"), b"app::login");
+        assert_eq!(field(&fields, "USER_ID"), b"42");
+        assert_eq!(field(&fields, "TRUSTED_KEY"), b"a\nb");
+        assert!(appender.take_error().is_none());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_own_fields() {
+        let (server, path) = server("own");
+        let mut appender = JournaldAppender::with_path(&path)
+            .unwrap()
+            .identifier("app");
+        let mut record = record("login");
+        record.fields = vec![
+            ("message".to_string(), FieldValue::Str("kv".to_string())),
+            ("priority".to_string(), FieldValue::I64(0)),
+            (
+                "syslog.identifier".to_string(),
+                FieldValue::Str("x".to_string()),
+            ),
+        ];
+        appender.do_logs(&[record]);
+        let mut buf = vec![0u8; 4096];
+        let n = server.recv(&mut buf).unwrap();
+        let fields = parse(&buf[..n]);
+        for name in ["MESSAGE", "PRIORITY", "SYSLOG_IDENTIFIER"] {
+            assert_eq!(fields.iter().filter(|(k, _)| k == name).count(), 1);
+        }
+        assert_eq!(field(&fields, "MESSAGE"), b"login");
+        assert_eq!(field(&fields, "PRIORITY"), b"4");
+        assert_eq!(field(&fields, "SYSLOG_IDENTIFIER"), b"app");
+        assert_eq!(field(&fields, "KV_MESSAGE"), b"kv");
+        assert_eq!(field(&fields, "KV_PRIORITY"), b"0");
+        assert_eq!(field(&fields, "KV_SYSLOG_IDENTIFIER"), b"x");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_reconnect() {
+        let (server, path) = server("reconnect");
+        let mut appender = JournaldAppender::with_path(&path)
+            .unwrap()
+            .backoff(Duration::from_millis(10), Duration::from_millis(20));
+        appender.do_logs(&[record("1")]);
+        let mut buf = vec![0u8; 4096];
+        let n = server.recv(&mut buf).unwrap();
+        assert_eq!(field(&parse(&buf[..n]), "MESSAGE"), b"1");
+        //journald is restarted
+        drop(server);
+        std::fs::remove_file(&path).unwrap();
+        appender.do_logs(&[record("2")]);
+        assert!(appender.take_error().is_some());
+        let server = UnixDatagram::bind(&path).unwrap();
+        std::thread::sleep(Duration::from_millis(50));
+        appender.do_logs(&[record("3")]);
+        assert!(appender.take_error().is_none());
+        let n = server.recv(&mut buf).unwrap();
+        assert_eq!(field(&parse(&buf[..n]), "MESSAGE"), b"3");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_memfd() {
+        let (server, path) = server("memfd");
+        let mut appender = JournaldAppender::with_path(&path).unwrap();
+        let args = "x".repeat(4 * 1024 * 1024);
+        appender.do_logs(&[record(&args)]);
+        assert!(appender.take_error().is_none());
+        let mut buf = [0u8; 16];
+        let mut space = [MaybeUninit::uninit(); rustix::cmsg_space!(ScmRights(1))];
+        let mut control = RecvAncillaryBuffer::new(&mut space);
+        let msg = recvmsg(
+            &server,
+            &mut [IoSliceMut::new(&mut buf)],
+            &mut control,
+            RecvFlags::empty(),
+        )
+        .unwrap();
+        assert_eq!(msg.bytes, 0);
+        let fd = control
+            .drain()
+            .find_map(|v| match v {
+                RecvAncillaryMessage::ScmRights(mut fds) => fds.next(),
+                _ => None,
+            })
+            .unwrap();
+        let mut file = File::from(fd);
+        file.seek(SeekFrom::Start(0)).unwrap();
+        let mut data = vec![];
+        file.read_to_end(&mut data).unwrap();
+        let fields = parse(&data);
+        assert_eq!(field(&fields, "MESSAGE"), args.as_bytes());
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,138 @@
+#[cfg(test)]
+mod test {
+    use fastlog::appender::{FastLogRecord, FieldValue, RecordFormat};
+    use fastlog::{FastLogFormatJson, JsonField};
+    use log::Level;
+    use serde_json::Value;
+
+    fn record(args: &str) -> FastLogRecord {
+        let kvs = [("user_id", 42)];
+        let mut record = FastLogRecord::from(
+            &log::Record::builder()
+                .level(Level::Warn)
+                .target("app")
+                .module_path_static(Some("app::login"))
+                .file(Some("src\\main.rs"))
+                .line(Some(7))
+                .key_values(&kvs)
+                .args(format_args!("{}", args))
+                .build(),
+        );
+        record
+            .fields
+            .push(("note".to_string(), FieldValue::Str(args.to_string())));
+        record
+            .fields
+            .push(("ratio".to_string(), FieldValue::F64(f64::NAN)));
+        record
+    }
+
+    fn parse(format: &FastLogFormatJson, record: &mut FastLogRecord) -> Value {
+        format.do_format(record);
+        assert!(record.formated.ends_with("}\n"));
+        assert_eq!(record.formated.matches('\n').count(), 1);
+        serde_json::from_str(&record.formated).unwrap()
+    }
+
+    #[test]
+    fn test_escape_round_trip() {
+        let mut args = String::from("quote\" backslash\\ slash/ tab\t new\nline é 😀 \u{2028}");
+        for c in 0u8..0x20 {
+            args.push(c as char);
+        }
+        args.push('\u{7f}');
+        for ascii in [false, true] {
+            let mut record = record(&args);
+            let v = parse(&FastLogFormatJson::new().set_ascii(ascii), &mut record);
+            assert_eq!(v["args"], args.as_str());
+            assert_eq!(v["note"], args.as_str());
+            assert_eq!(v["file"], "src\\main.rs");
+            assert_eq!(v["level"], "WARN");
+            assert_eq!(v["line"], 7);
+            assert_eq!(v["user_id"], 42);
+            assert_eq!(v["ratio"], Value::Null);
+            if ascii {
+                assert!(record.formated.is_ascii());
+                assert!(record.formated.contains("\\ud83d\\ude00"));
+            }
+        }
+    }
+
+    #[test]
+    fn test_escape_control() {
+        let mut out = String::new();
+        fastlog::write_json_str(&mut out, "a\u{0}\u{1f}\u{8}\u{c}\"", false);
+        assert_eq!(out, "\"a\\u0000\\u001f\\b\\f\\\"\"");
+    }
+
+    #[test]
+    fn test_fields() {
+        let format = FastLogFormatJson::new()
+            .set_fields(vec![JsonField::Level, JsonField::Args])
+            .rename_field(JsonField::Date, "@timestamp")
+            .add_field(JsonField::Target)
+            .add_field(JsonField::ModulePath)
+            .add_field(JsonField::Thread)
+            .rename_field(JsonField::Field("user_id".to_string()), "uid")
+            .nest_fields("fields")
+            .remove_field(&JsonField::Level);
+        let mut record = std::thread::Builder::new()
+            .name("worker-1".to_string())
+            .spawn(|| record("login"))
+            .unwrap()
+            .join()
+            .unwrap();
+        let v = parse(&format, &mut record);
+        //members are written in the order they were chosen
+        let keys = [
+            "args",
+            "@timestamp",
+            "target",
+            "module_path",
+            "thread",
+            "uid",
+            "fields",
+        ];
+        let at: Vec<usize> = keys
+            .iter()
+            .map(|k| record.formated.find(&format!("\"{}\":", k)).unwrap())
+            .collect();
+        assert!(at.windows(2).all(|v| v[0] < v[1]));
+        assert_eq!(v.as_object().unwrap().len(), keys.len());
+        assert_eq!(v["target"], "app");
+        assert_eq!(v["module_path"], "app::login");
+        assert_eq!(v["thread"], "worker-1");
+        assert_eq!(v["uid"], 42);
+        assert_eq!(
+            v["fields"],
+            serde_json::json!({"note": "login", "ratio": null})
+        );
+    }
+
+    #[test]
+    fn test_flat_fields_collision() {
+        let format = FastLogFormatJson::new()
+            .add_field(JsonField::Target)
+            .rename_field(JsonField::Field("user_id".to_string()), "uid");
+        let mut record = record("login");
+        for key in ["level", "args", "target", "uid"] {
+            record
+                .fields
+                .push((key.to_string(), FieldValue::Str(format!("kv {}", key))));
+        }
+        let v = parse(&format, &mut record);
+        let members = v.as_object().unwrap();
+        //serde_json keeps the last duplicate,so count the keys in the text
+        assert_eq!(record.formated.matches("\"level\":").count(), 1);
+        assert_eq!(members.len(), 13);
+        assert_eq!(v["level"], "WARN");
+        assert_eq!(v["args"], "login");
+        assert_eq!(v["target"], "app");
+        assert_eq!(v["uid"], 42);
+        assert_eq!(v["fields.level"], "kv level");
+        assert_eq!(v["fields.args"], "kv args");
+        assert_eq!(v["fields.target"], "kv target");
+        assert_eq!(v["fields.uid"], "kv uid");
+        assert_eq!(v["note"], "login");
+    }
+}
@@ -0,0 +1,53 @@
+#[cfg(test)]
+mod test {
+    use fastlog::appender::{FastLogRecord, FieldValue, RecordFormat};
+    use fastlog::{FastLogFormat, FastLogFormatJson};
+    use log::Level;
+
+    fn record() -> FastLogRecord {
+        let kvs = [("user_id", 42)];
+        let mut record = FastLogRecord::from(
+            &log::Record::builder()
+                .level(Level::Info)
+                .file(Some("src/main.rs"))
+                .line(Some(1))
+                .key_values(&kvs)
+                .args(format_args!("login"))
+                .build(),
+        );
+        record
+            .fields
+            .push(("tenant".to_string(), FieldValue::Str("a b".to_string())));
+        record
+            .fields
+            .push(("ok".to_string(), FieldValue::Bool(true)));
+        record
+    }
+
+    #[test]
+    fn test_capture_fields() {
+        let record = record();
+        assert_eq!(record.args, "login");
+        assert_eq!(record.field("user_id"), Some(&FieldValue::I64(42)));
+        assert_eq!(record.fields[1].0, "tenant");
+        assert_eq!(record.field("none"), None);
+    }
+
+    #[test]
+    fn test_format_fields() {
+        let mut record = record();
+        FastLogFormat::new().do_format(&mut record);
+        assert!(record
+            .formated
+            .ends_with("login user_id=42 tenant=\"a b\" ok=true\n"));
+    }
+
+    #[test]
+    fn test_format_json_fields() {
+        let mut record = record();
+        FastLogFormatJson::new().do_format(&mut record);
+        assert!(record
+            .formated
+            .ends_with("\"line\":1,\"user_id\":42,\"tenant\":\"a b\",\"ok\":true}\n"));
+    }
+}
@@ -0,0 +1,120 @@
+#[cfg(test)]
+mod test {
+    use fastlog::appender::{FastLogRecord, FieldValue, RecordFormat};
+    use fastlog::{LogfmtFormat, TimeType, TimestampStyle};
+    use log::Level;
+    use std::time::{Duration, SystemTime};
+
+    fn record(args: &str) -> FastLogRecord {
+        let kvs = [("user_id", 42)];
+        let mut record = FastLogRecord::from(
+            &log::Record::builder()
+                .level(Level::Warn)
+                .target("app")
+                .module_path_static(Some("app::login"))
+                .file_static(Some("src/login.rs"))
+                .line(Some(12))
+                .key_values(&kvs)
+                .args(format_args!("{}", args))
+                .build(),
+        );
+        record
+            .fields
+            .push(("note".to_string(), FieldValue::Str(args.to_string())));
+        record
+            .fields
+            .push(("bad key=\"".to_string(), FieldValue::Bool(true)));
+        record.now = SystemTime::UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789);
+        record
+    }
+
+    /// parse a logfmt line,quoted values are json strings
+    fn parse(line: &str) -> Vec<(String, String)> {
+        let line = line.strip_suffix('\n').unwrap();
+        assert!(!line.contains('\n'));
+        let mut pairs = vec![];
+        let mut rest = line;
+        while !rest.is_empty() {
+            let eq = rest.find('=').unwrap();
+            let key = rest[..eq].to_string();
+            rest = &rest[eq + 1..];
+            let end = if rest.starts_with('"') {
+                let mut escape = false;
+                rest.char_indices()
+                    .skip(1)
+                    .find(|(_, c)| {
+                        let end = *c == '"' && !escape;
+                        escape = *c == '\\' && !escape;
+                        end
+                    })
+                    .unwrap()
+                    .0
+                    + 1
+            } else {
+                rest.find(' ').unwrap_or(rest.len())
+            };
+            let value = if rest.starts_with('"') {
+                serde_json::from_str(&rest[..end]).unwrap()
+            } else {
+                rest[..end].to_string()
+            };
+            pairs.push((key, value));
+            rest = rest[end..].strip_prefix(' ').unwrap_or(&rest[end..]);
+        }
+        pairs
+    }
+
+    #[test]
+    fn test_logfmt() {
+        let mut record = record("login");
+        LogfmtFormat::new()
+            .set_time_type(TimeType::Utc)
+            .do_format(&mut record);
+        assert_eq!(
+            record.formated,
+            "ts=2023-11-14T22:13:20.123Z level=warn msg=login module=app::login user_id=42 note=login badkey=true\n"
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let args = "a \"quoted\" msg\\ with\ttab\nnew line =, é \u{1}\u{7f}";
+        let mut record = record(args);
+        LogfmtFormat::new()
+            .set_time_type(TimeType::Utc)
+            .set_timestamp(TimestampStyle::Stand)
+            .set_target(true)
+            .set_file(true)
+            .set_thread(true)
+            .do_format(&mut record);
+        let pairs = parse(&record.formated);
+        let keys: Vec<&str> = pairs.iter().map(|(k, _)| k.as_str()).collect();
+        assert_eq!(
+            keys,
+            [
+                "ts", "level", "msg", "target", "module", "file", "line", "thread", "user_id",
+                "note", "badkey"
+            ]
+        );
+        assert_eq!(pairs[0].1, "2023-11-14 22:13:20.123");
+        assert_eq!(pairs[2].1, args);
+        assert_eq!(pairs[9].1, args);
+        assert_eq!(pairs[6].1, "12");
+    }
+
+    #[test]
+    fn test_timestamp() {
+        let mut record = record("");
+        for (style, ts) in [
+            (TimestampStyle::UnixSecs, "ts=1700000000 "),
+            (TimestampStyle::UnixMillis, "ts=1700000000123 "),
+        ] {
+            LogfmtFormat::new()
+                .set_timestamp(style)
+                .set_module_path(false)
+                .do_format(&mut record);
+            assert!(record.formated.starts_with(ts));
+            assert!(record.formated.contains(" msg=\"\" user_id=42"));
+        }
+    }
+}
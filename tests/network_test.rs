@@ -0,0 +1,170 @@
+#[cfg(test)]
+mod test {
+    use fastlog::appender::{Command, FastLogRecord, LogAppender};
+    use fastlog::plugin::network::{Framing, NetworkAppender};
+    use std::io::{BufRead, BufReader, Read};
+    use std::net::{TcpListener, UdpSocket};
+    use std::sync::atomic::Ordering;
+    use std::time::Duration;
+
+    fn records(lines: &[&str]) -> Vec<FastLogRecord> {
+        lines
+            .iter()
+            .map(|v| {
+                let mut record = FastLogRecord::new_command(Command::CommandRecord);
+                record.formated = v.to_string();
+                record
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_tcp_newline() {
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut appender = NetworkAppender::tcp(&server.local_addr().unwrap().to_string());
+        let counters = appender.counters();
+        appender.do_logs(&records(&["a\n", "b"]));
+        appender.do_logs(&[FastLogRecord::new_command(Command::CommandExit)]);
+        drop(appender);
+        let (stream, _) = server.accept().unwrap();
+        let lines: Vec<String> = BufReader::new(stream).lines().map(|v| v.unwrap()).collect();
+        assert_eq!(lines, ["a", "b"]);
+        assert_eq!(counters.sent_records.load(Ordering::Relaxed), 2);
+        assert_eq!(counters.sent_bytes.load(Ordering::Relaxed), 4);
+        assert_eq!(counters.connects.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_length_prefixed() {
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut appender = NetworkAppender::tcp(&server.local_addr().unwrap().to_string())
+            .framing(Framing::LengthPrefixed);
+        appender.do_logs(&records(&["hello\n", "multi\nline\n"]));
+        drop(appender);
+        let (mut stream, _) = server.accept().unwrap();
+        let mut data = vec![];
+        stream.read_to_end(&mut data).unwrap();
+        assert_eq!(data, b"\0\0\0\x06hello\n\0\0\0\x0bmulti\nline\n");
+    }
+
+    #[test]
+    fn test_reconnect_buffer() {
+        //a closed port
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let mut appender = NetworkAppender::tcp(&addr.to_string())
+            .buffer_size(8)
+            .backoff(Duration::from_millis(10), Duration::from_millis(20));
+        let counters = appender.counters();
+        appender.do_logs(&records(&["1\n", "2\n", "3\n", "4\n"]));
+        appender.do_logs(&records(&["5\n", "6\n"]));
+        assert!(appender.take_error().is_some());
+        assert_eq!(counters.buffered_bytes.load(Ordering::Relaxed), 8);
+        assert_eq!(counters.dropped_records.load(Ordering::Relaxed), 2);
+        assert_eq!(counters.dropped_bytes.load(Ordering::Relaxed), 4);
+        let server = TcpListener::bind(addr).unwrap();
+        std::thread::sleep(Duration::from_millis(50));
+        appender.do_logs(&records(&["7\n"]));
+        drop(appender);
+        let (stream, _) = server.accept().unwrap();
+        let lines: Vec<String> = BufReader::new(stream).lines().map(|v| v.unwrap()).collect();
+        assert_eq!(lines, ["1", "2", "3", "4", "7"]);
+        assert_eq!(counters.buffered_bytes.load(Ordering::Relaxed), 0);
+        assert_eq!(counters.sent_records.load(Ordering::Relaxed), 5);
+    }
+
+    #[test]
+    fn test_newline_multi_line_record() {
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut appender = NetworkAppender::tcp(&server.local_addr().unwrap().to_string());
+        let counters = appender.counters();
+        appender.do_logs(&records(&["multi\nline\n", "b\n"]));
+        drop(appender);
+        let (stream, _) = server.accept().unwrap();
+        let lines: Vec<String> = BufReader::new(stream).lines().map(|v| v.unwrap()).collect();
+        assert_eq!(lines, ["multi", "line", "b"]);
+        assert_eq!(counters.sent_records.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_idle_tick() {
+        //a closed port
+        let addr = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let mut appender = NetworkAppender::tcp(&addr.to_string())
+            .backoff(Duration::from_millis(10), Duration::from_millis(20));
+        let counters = appender.counters();
+        assert!(appender.next_tick().is_none());
+        appender.do_logs(&records(&["1\n", "2\n"]));
+        assert!(appender.take_error().is_some());
+        let server = TcpListener::bind(addr).unwrap();
+        let wait = appender.next_tick().unwrap();
+        assert!(wait <= Duration::from_millis(20));
+        std::thread::sleep(wait);
+        appender.tick();
+        assert!(appender.next_tick().is_none());
+        assert_eq!(counters.buffered_bytes.load(Ordering::Relaxed), 0);
+        assert_eq!(counters.sent_records.load(Ordering::Relaxed), 2);
+        drop(appender);
+        let (stream, _) = server.accept().unwrap();
+        let lines: Vec<String> = BufReader::new(stream).lines().map(|v| v.unwrap()).collect();
+        assert_eq!(lines, ["1", "2"]);
+    }
+
+    #[test]
+    fn test_udp_oversized() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        server
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut appender = NetworkAppender::udp(&server.local_addr().unwrap().to_string())
+            .backoff(Duration::from_secs(60), Duration::from_secs(60));
+        let counters = appender.counters();
+        let large = "a".repeat(100 * 1024);
+        appender.do_logs(&records(&[&large, "b\n"]));
+        assert!(appender.take_error().is_some());
+        let mut buf = [0u8; 64];
+        let n = server.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"b\n");
+        assert_eq!(counters.sent_records.load(Ordering::Relaxed), 1);
+        assert_eq!(counters.dropped_records.load(Ordering::Relaxed), 1);
+        assert_eq!(counters.connects.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn test_udp() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        server
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut appender = NetworkAppender::udp(&server.local_addr().unwrap().to_string());
+        appender.do_logs(&records(&["a\n", "b\n"]));
+        let mut buf = [0u8; 64];
+        let n = server.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"a\n");
+        let n = server.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..n], b"b\n");
+        assert_eq!(appender.counters().sent_records.load(Ordering::Relaxed), 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unix() {
+        use std::os::unix::net::UnixListener;
+        let _ = std::fs::create_dir_all("target/test");
+        let path = format!("target/test/network_{}.sock", std::process::id());
+        let _ = std::fs::remove_file(&path);
+        let server = UnixListener::bind(&path).unwrap();
+        let mut appender = NetworkAppender::unix(&path);
+        appender.do_logs(&records(&["a\n"]));
+        drop(appender);
+        let (stream, _) = server.accept().unwrap();
+        let lines: Vec<String> = BufReader::new(stream).lines().map(|v| v.unwrap()).collect();
+        assert_eq!(lines, ["a"]);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,62 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::Collect;
+    use fastlog::{Config, OverflowPolicy};
+    use std::time::Duration;
+
+    fn run(overflow: OverflowPolicy, level: log::Level) -> (Vec<String>, u64) {
+        let records = Collect::new().sleep(Duration::from_millis(5));
+        fastlog::init(
+            Config::new()
+                .chan_len(Some(10))
+                .overflow(overflow)
+                .custom(records.clone()),
+        )
+        .unwrap();
+        for i in 0..1000 {
+            log::log!(level, "{}", i);
+        }
+        fastlog::shutdown(None).unwrap();
+        let mut args = vec![];
+        let mut dropped = 0;
+        for x in records.records() {
+            if x.target == "fastlog" {
+                dropped += x
+                    .args
+                    .trim_end_matches(" records dropped")
+                    .parse::<u64>()
+                    .unwrap();
+            } else {
+                args.push(x.args.to_string());
+            }
+        }
+        (args, dropped)
+    }
+
+    #[test]
+    fn test_overflow() {
+        let (args, dropped) = run(OverflowPolicy::DropNewest, log::Level::Info);
+        assert!(dropped > 0);
+        assert_eq!(args.len() as u64 + dropped, 1000);
+        assert_eq!(args[0], "0");
+
+        let (args, dropped) = run(OverflowPolicy::DropOldest, log::Level::Info);
+        assert!(dropped > 0);
+        assert_eq!(args.len() as u64 + dropped, 1000);
+        assert_eq!(args.last().unwrap(), "999");
+
+        let (args, dropped) = run(OverflowPolicy::ShedByLevel, log::Level::Debug);
+        assert!(dropped > 0);
+        assert_eq!(args.len() as u64 + dropped, 1000);
+
+        let (args, dropped) = run(OverflowPolicy::ShedByLevel, log::Level::Error);
+        assert_eq!(dropped, 0);
+        assert_eq!(args.len(), 1000);
+
+        let (args, dropped) = run(OverflowPolicy::Block, log::Level::Info);
+        assert_eq!(dropped, 0);
+        assert_eq!(args.len(), 1000);
+    }
+}
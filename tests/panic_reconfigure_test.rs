@@ -0,0 +1,19 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::Collect;
+    use fastlog::Config;
+
+    #[test]
+    fn test_reconfigure_capture_panics() {
+        fastlog::init(Config::new().custom(Collect::new())).unwrap();
+        let records = Collect::new();
+        fastlog::reconfigure(Config::new().custom(records.clone()).capture_panics()).unwrap();
+        let result = std::thread::spawn(|| panic!("boom")).join();
+        assert!(result.is_err());
+        let records = records.records();
+        let record = records.iter().find(|x| x.target == "panic").unwrap();
+        assert!(record.args.contains(": boom\nstack backtrace:\n"));
+    }
+}
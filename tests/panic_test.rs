@@ -0,0 +1,29 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::Collect;
+    use fastlog::Config;
+    use log::Level;
+
+    #[test]
+    fn test_capture_panics() {
+        let records = Collect::new();
+        fastlog::init(Config::new().custom(records.clone()).capture_panics()).unwrap();
+        let result = std::thread::Builder::new()
+            .name("worker".to_string())
+            .spawn(|| panic!("boom {}", 1))
+            .unwrap()
+            .join();
+        assert!(result.is_err());
+        //the hook flushed before the thread exit
+        let records = records.records();
+        let record = records.iter().find(|x| x.target == "panic").unwrap();
+        assert_eq!(record.level, Level::Error);
+        assert!(record.file.ends_with("panic_test.rs"));
+        assert!(record
+            .args
+            .starts_with("thread 'worker' panicked at tests/panic_test.rs:"));
+        assert!(record.args.contains(": boom 1\nstack backtrace:\n"));
+    }
+}
@@ -0,0 +1,81 @@
+#[cfg(test)]
+mod test {
+    use fastlog::appender::{FastLogRecord, FieldValue, RecordFormat};
+    use fastlog::{PatternFormat, TimeType};
+    use log::Level;
+    use std::time::{Duration, SystemTime};
+
+    fn record() -> FastLogRecord {
+        let kvs = [("user_id", 42)];
+        let mut record = FastLogRecord::from(
+            &log::Record::builder()
+                .level(Level::Info)
+                .target("app")
+                .module_path_static(Some("app::login"))
+                .file_static(Some("src/login.rs"))
+                .line(Some(12))
+                .key_values(&kvs)
+                .args(format_args!("login"))
+                .build(),
+        );
+        record
+            .fields
+            .push(("tenant".to_string(), FieldValue::Str("a b".to_string())));
+        record.now = SystemTime::UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789);
+        record
+    }
+
+    fn format(pattern: &str, record: &mut FastLogRecord) -> String {
+        PatternFormat::new(pattern)
+            .unwrap()
+            .set_time_type(TimeType::Utc)
+            .do_format(record);
+        record.formated.clone()
+    }
+
+    #[test]
+    fn test_pattern() {
+        let mut named = std::thread::Builder::new()
+            .name("worker".to_string())
+            .spawn(record)
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(
+            format("%d{%Y-%m-%dT%H:%M:%S%.3f%:z} %-5l %t [%M] %m%n", &mut named),
+            "2023-11-14T22:13:20.123+00:00 INFO  worker [app::login] login\n"
+        );
+        assert_eq!(
+            format("%d %d{%s.%f %z %%} %T %c %f:%L 100%%", &mut named),
+            "2023-11-14 22:13:20.123 1700000000.123456789 +0000 % app app src/login.rs:12 100%"
+        );
+        let mut unnamed = std::thread::spawn(record).join().unwrap();
+        assert!(format("%t", &mut unnamed).starts_with("ThreadId("));
+    }
+
+    #[test]
+    fn test_pad() {
+        let mut record = record();
+        assert_eq!(
+            format("[%5l][%-6p][%.-3M][%.5M][%8.3m][%2m]", &mut record),
+            "[ INFO][INFO  ][app][login][     gin][login]"
+        );
+    }
+
+    #[test]
+    fn test_fields() {
+        let mut record = record();
+        assert_eq!(
+            format("%m %X|%X{user_id}|%X{none}|%-4X{tenant}|", &mut record),
+            "login user_id=42 tenant=\"a b\"|42||a b |"
+        );
+    }
+
+    #[test]
+    fn test_error() {
+        for pattern in ["%q", "%d{%Q}", "%d{%.2f}", "%X{user_id", "%.m", "%m %"] {
+            assert!(PatternFormat::new(pattern).is_err(), "{}", pattern);
+        }
+        assert!(PatternFormat::new("%l{%m}").is_ok());
+    }
+}
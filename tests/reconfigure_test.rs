@@ -0,0 +1,79 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::Collect;
+    use fastlog::appender::{Command, FastLogRecord, LogAppender};
+    use fastlog::stats::Diagnostic;
+    use fastlog::{AppenderConfig, Config, FastLogFormatJson};
+    use std::sync::mpsc::{channel, Receiver};
+    use std::sync::{Arc, Mutex};
+    use std::time::{Duration, Instant};
+
+    /// stall on the first batch with records until released
+    struct Stuck {
+        release: Receiver<()>,
+    }
+
+    impl LogAppender for Stuck {
+        fn do_logs(&mut self, records: &[FastLogRecord]) {
+            if records.iter().any(|x| x.command == Command::CommandRecord) {
+                let _ = self.release.recv();
+            }
+        }
+    }
+
+    #[test]
+    fn test_reconfigure() {
+        let old = Collect::new();
+        let new = Collect::new();
+        fastlog::init(Config::new().custom(old.clone())).unwrap();
+        for i in 0..1000 {
+            log::info!("old {}", i);
+        }
+        fastlog::reconfigure(
+            Config::new().append(AppenderConfig::new(new.clone()).format(FastLogFormatJson::new())),
+        )
+        .unwrap();
+        assert!(old.is_dropped());
+        log::info!("new");
+        log::logger().flush();
+        //the records sent before are written by the old appender with the old format
+        let expect: Vec<String> = (0..1000).map(|i| format!("old {}", i)).collect();
+        assert_eq!(old.args(), expect);
+        assert!(old.lines()[0].ends_with(" old 0\n"));
+        assert_eq!(new.args(), vec!["new"]);
+        assert!(new.lines()[0].starts_with("{\"args\":\"new\""));
+
+        //reconfigure away from a stalled appender
+        let (release, r) = channel();
+        let undrained = Arc::new(Mutex::new(vec![]));
+        let diagnostics = undrained.clone();
+        fastlog::reconfigure(
+            Config::new()
+                .diagnostics(move |x| {
+                    if let Diagnostic::Undrained { appender } = x {
+                        diagnostics.lock().unwrap().push(appender.clone());
+                    }
+                })
+                .append(AppenderConfig::new(Stuck { release: r }).name("stuck")),
+        )
+        .unwrap();
+        log::info!("stuck");
+        let last = Collect::new();
+        let start = Instant::now();
+        fastlog::reconfigure(
+            Config::new()
+                .drain_timeout(Duration::from_millis(100))
+                .custom(last.clone()),
+        )
+        .unwrap();
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(*undrained.lock().unwrap(), vec!["stuck".to_string()]);
+        log::info!("last");
+        log::logger().flush();
+        assert_eq!(last.args(), vec!["last"]);
+        drop(release);
+        fastlog::exit().unwrap();
+    }
+}
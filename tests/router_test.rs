@@ -0,0 +1,88 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::{record, Collect};
+    use fastlog::appender::{Command, FastLogRecord, FieldValue, LogAppender};
+    use fastlog::plugin::router::{RouteRule, RouterAppender};
+    use log::Level;
+
+    fn in_module(mut record: FastLogRecord, module: &str) -> FastLogRecord {
+        record.module_path = module.to_string().into();
+        record
+    }
+
+    #[test]
+    fn test_route() {
+        let (audit, db, errors, app) = (
+            Collect::new(),
+            Collect::new(),
+            Collect::new(),
+            Collect::new(),
+        );
+        let mut router = RouterAppender::new()
+            .default_route(app.clone())
+            .route(RouteRule::Target("audit".to_string()), audit.clone())
+            .route(RouteRule::Module("app::db::*".to_string()), db.clone())
+            .route(RouteRule::Level(Level::Warn, Level::Error), errors.clone());
+        router.do_logs(&[
+            in_module(record(Level::Info, "audit::login", "a1"), "app"),
+            in_module(record(Level::Info, "app", "d1"), "app::db::pool"),
+            in_module(record(Level::Error, "app", "e1"), "app::db"),
+            in_module(record(Level::Info, "app", "x1"), "app"),
+            FastLogRecord::new_command(Command::CommandExit),
+            in_module(record(Level::Warn, "audit", "a2"), "app"),
+            in_module(record(Level::Debug, "app", "x2"), "app::http"),
+        ]);
+        assert_eq!(audit.take_batches(), [["a1", "<command>", "a2"]]);
+        assert_eq!(db.take_batches(), [["d1", "<command>"]]);
+        assert_eq!(errors.take_batches(), [["e1", "<command>"]]);
+        assert_eq!(app.take_batches(), [["x1", "<command>", "x2"]]);
+
+        //a batch of a route is passed as it is,the others get the commands
+        router.do_logs(&[
+            in_module(record(Level::Info, "audit", "a3"), "app"),
+            FastLogRecord::new_command(Command::CommandExit),
+        ]);
+        assert_eq!(audit.take_batches(), [["a3", "<command>"]]);
+        assert_eq!(app.take_batches(), [["<command>"]]);
+        router.do_logs(&[in_module(record(Level::Info, "audit", "a4"), "app")]);
+        assert_eq!(audit.take_batches(), [["a4"]]);
+        assert!(app.take_batches().is_empty());
+    }
+
+    #[test]
+    fn test_rules() {
+        let mut record = in_module(record(Level::Info, "app", ""), "app::db::pool");
+        record.fields = vec![
+            ("tenant".to_string(), FieldValue::Str("a".to_string())),
+            ("code".to_string(), FieldValue::I64(500)),
+        ];
+        assert!(RouteRule::Module("app::*::pool".to_string()).matches(&record));
+        assert!(RouteRule::Module("app::d?::*".to_string()).matches(&record));
+        assert!(!RouteRule::Module("app::db".to_string()).matches(&record));
+        assert!(RouteRule::Level(Level::Debug, Level::Info).matches(&record));
+        assert!(!RouteRule::Level(Level::Error, Level::Warn).matches(&record));
+        assert!(RouteRule::Field("tenant".to_string(), "a".to_string()).matches(&record));
+        assert!(RouteRule::Field("code".to_string(), "500".to_string()).matches(&record));
+        assert!(RouteRule::HasField("code".to_string()).matches(&record));
+        assert!(RouteRule::All(vec![
+            RouteRule::Target("app".to_string()),
+            RouteRule::Not(Box::new(RouteRule::HasField("none".to_string()))),
+        ])
+        .matches(&record));
+        assert!(!RouteRule::Any(vec![]).matches(&record));
+    }
+
+    #[test]
+    fn test_no_default() {
+        let audit = Collect::new();
+        let mut router =
+            RouterAppender::new().route(RouteRule::Target("audit".to_string()), audit.clone());
+        router.do_logs(&[
+            in_module(record(Level::Info, "app", "x"), "app"),
+            in_module(record(Level::Info, "audit", "a"), "app"),
+        ]);
+        assert_eq!(audit.take_batches(), [["a"]]);
+    }
+}
@@ -0,0 +1,57 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::Collect;
+    use fastlog::Config;
+    use std::time::Duration;
+
+    #[test]
+    fn test_sharded_flush_race() {
+        let lines = Collect::new();
+        //the shards are only handed off by flush and exit
+        fastlog::init(
+            Config::new()
+                .custom(lines.clone())
+                .chan_len(Some(1000))
+                .sharded(64, Duration::from_secs(60)),
+        )
+        .unwrap();
+        let threads: Vec<_> = (0..8)
+            .map(|t| {
+                let lines = lines.clone();
+                std::thread::spawn(move || {
+                    for round in 0..50 {
+                        for i in 0..10 {
+                            log::info!("{} {} {}", t, round, i);
+                        }
+                        fastlog::flush().unwrap().wait();
+                        let last = format!("{} {} 9", t, round);
+                        assert!(lines.args().contains(&last), "{} not flushed", last);
+                    }
+                })
+            })
+            .collect();
+        for x in threads {
+            x.join().unwrap();
+        }
+        let threads: Vec<_> = (0..8)
+            .map(|t| {
+                std::thread::spawn(move || {
+                    for i in 0..10 {
+                        log::info!("exit {} {}", t, i);
+                    }
+                })
+            })
+            .collect();
+        for x in threads {
+            x.join().unwrap();
+        }
+        fastlog::exit().unwrap();
+        let args = lines.args();
+        assert_eq!(args.len(), 8 * 50 * 10 + 8 * 10);
+        for t in 0..8 {
+            assert!(args.contains(&format!("exit {} 9", t)));
+        }
+    }
+}
@@ -0,0 +1,58 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::Collect;
+    use fastlog::Config;
+    use std::thread::sleep;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_sharded() {
+        let lines = Collect::new();
+        fastlog::init(
+            Config::new()
+                .custom(lines.clone())
+                .chan_len(Some(1000))
+                .sharded(64, Duration::from_millis(20)),
+        )
+        .unwrap();
+        //a quiet shard is taken after max_staleness
+        log::info!("quiet");
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while lines.is_empty() && Instant::now() < deadline {
+            sleep(Duration::from_millis(1));
+        }
+        assert_eq!(lines.args(), vec!["quiet".to_string()]);
+        lines.clear();
+
+        //the records of a thread keep order
+        let threads: Vec<_> = (0..8)
+            .map(|t| {
+                std::thread::spawn(move || {
+                    for i in 0..1000 {
+                        log::info!("{} {}", t, i);
+                    }
+                })
+            })
+            .collect();
+        for x in threads {
+            x.join().unwrap();
+        }
+        fastlog::flush().unwrap().wait();
+        let mut next = [0; 8];
+        for line in lines.args() {
+            let (t, i) = line.split_once(' ').unwrap();
+            let t: usize = t.parse().unwrap();
+            assert_eq!(i.parse::<i32>().unwrap(), next[t]);
+            next[t] += 1;
+        }
+        assert_eq!(next, [1000; 8]);
+        lines.clear();
+
+        //exit hand off the shards
+        log::info!("last");
+        fastlog::exit().unwrap();
+        assert_eq!(lines.args(), vec!["last".to_string()]);
+    }
+}
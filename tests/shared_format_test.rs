@@ -0,0 +1,56 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::Collect;
+    use fastlog::appender::{Command, FastLogRecord, RecordFormat};
+    use fastlog::{AppenderConfig, Config, FastLogFormatJson};
+    use log::LevelFilter;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// count the records formatted
+    struct Counting {
+        count: Arc<AtomicUsize>,
+        json: FastLogFormatJson,
+    }
+
+    impl RecordFormat for Counting {
+        fn do_format(&self, arg: &mut FastLogRecord) {
+            if arg.command == Command::CommandRecord {
+                self.count.fetch_add(1, Ordering::SeqCst);
+            }
+            self.json.do_format(arg);
+        }
+    }
+
+    #[test]
+    fn test_shared_format() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let json: Arc<dyn RecordFormat> = Arc::new(Counting {
+            count: count.clone(),
+            json: FastLogFormatJson::new(),
+        });
+        let (all, warn, other) = (Collect::new(), Collect::new(), Collect::new());
+        fastlog::init(
+            Config::new()
+                .append(AppenderConfig::new(all.clone()).shared_format(json.clone()))
+                .append(
+                    AppenderConfig::new(warn.clone())
+                        .level(LevelFilter::Warn)
+                        .shared_format(json),
+                )
+                .append(AppenderConfig::new(other.clone())),
+        )
+        .unwrap();
+        log::info!("a");
+        log::warn!("b");
+        fastlog::flush().unwrap().wait();
+        //formatted once for both appenders
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+        assert_eq!(all.args(), vec!["a", "b"]);
+        assert_eq!(warn.args(), vec!["b"]);
+        assert!(warn.lines()[0].starts_with("{\"args\":\"b\""));
+        assert!(other.lines()[0].ends_with(" a\n"));
+    }
+}
@@ -0,0 +1,38 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::Collect;
+    use fastlog::{AppenderConfig, Config};
+    use std::time::Duration;
+
+    #[test]
+    fn test_shutdown_and_init() {
+        let records = Collect::new().sleep(Duration::from_millis(1));
+        fastlog::init(Config::new().chan_len(Some(100)).custom(records.clone())).unwrap();
+        assert!(fastlog::init(Config::new().console()).is_err());
+        for i in 0..1000 {
+            log::info!("{}", i);
+        }
+        let report = fastlog::shutdown(Some(Duration::from_secs(10))).unwrap();
+        assert!(report.is_drained());
+        assert_eq!(records.len(), 1000);
+        //ignored after shutdown
+        log::info!("ignored");
+        assert!(fastlog::flush().is_err());
+        assert!(fastlog::shutdown(None).is_err());
+
+        //init again,the slow appender can not drain before timeout
+        let records = Collect::new().sleep(Duration::from_secs(1));
+        fastlog::init(Config::new().append(AppenderConfig::new(records).name("slow"))).unwrap();
+        log::info!("slow");
+        let report = fastlog::shutdown(Some(Duration::from_millis(100))).unwrap();
+        assert_eq!(report.undrained, vec!["slow".to_string()]);
+
+        let records = Collect::new();
+        fastlog::init(Config::new().custom(records.clone())).unwrap();
+        log::info!("again");
+        fastlog::exit().unwrap();
+        assert_eq!(records.len(), 1);
+    }
+}
@@ -24,13 +24,16 @@ mod test {
         appender.do_logs(&[FastLogRecord {
             command: Command::CommandRecord,
             level: Level::Error,
-            target: "".to_string(),
-            args: "".to_string(),
-            module_path: "".to_string(),
-            file: "".to_string(),
+            target: "".into(),
+            args: "".into(),
+            module_path: "".into(),
+            file: "".into(),
             line: None,
             now: SystemTime::now(),
             formated: "".to_string(),
+            fields: vec![],
+            thread: None,
+            level_span: None,
         }]);
         appender.send_pack(appender.temp_name().replace(".log", &DateTime::now().format("YYYY-MM-DDThh-mm-ss.000000.log")), None);
         sleep(Duration::from_secs(1));
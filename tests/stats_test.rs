@@ -0,0 +1,92 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::Collect;
+    use fastlog::appender::{FastLogRecord, LogAppender};
+    use fastlog::error::LogError;
+    use fastlog::filter::Filter;
+    use fastlog::{AppenderConfig, Config};
+    use std::time::Duration;
+
+    /// fail every write
+    struct Broken {}
+
+    impl LogAppender for Broken {
+        fn do_logs(&mut self, _records: &[FastLogRecord]) {}
+
+        fn take_error(&mut self) -> Option<LogError> {
+            Some(LogError::from("disk full"))
+        }
+    }
+
+    /// write every record,then report an error
+    struct Flaky {
+        lines: Collect,
+    }
+
+    impl LogAppender for Flaky {
+        fn do_logs(&mut self, records: &[FastLogRecord]) {
+            self.lines.do_logs(records);
+        }
+
+        fn take_error(&mut self) -> Option<LogError> {
+            Some(LogError::from("timeout"))
+        }
+    }
+
+    struct SkipFilter {}
+
+    impl Filter for SkipFilter {
+        fn do_log(&self, record: &log::Record) -> bool {
+            record.args().to_string() != "skip"
+        }
+    }
+
+    #[test]
+    fn test_stats() {
+        assert!(!fastlog::stats().running);
+        let (written, fallback) = (Collect::new(), Collect::new());
+        fastlog::init(
+            Config::new()
+                .chan_len(Some(100))
+                .add_filter(SkipFilter {})
+                .append(AppenderConfig::new(Broken {}).name("broken"))
+                .append(
+                    AppenderConfig::new(Flaky {
+                        lines: written.clone(),
+                    })
+                    .name("flaky")
+                    .fallback(fallback.clone()),
+                ),
+        )
+        .unwrap();
+        for i in 0..10 {
+            log::info!("{}", i);
+        }
+        log::info!("skip");
+        fastlog::flush().unwrap().wait();
+        let stats = fastlog::stats();
+        assert!(stats.running);
+        assert!(stats.is_healthy());
+        assert_eq!(stats.queue_cap, Some(100));
+        assert_eq!(stats.accepted, 10);
+        assert_eq!(stats.filtered, 1);
+        assert_eq!(stats.dropped, 0);
+        let broken = &stats.appenders[0];
+        assert_eq!(broken.name, "broken");
+        assert!(broken.batches > 0);
+        assert!(broken.bytes > 0);
+        assert_eq!(broken.last_error, Some("disk full".to_string()));
+        //an error after the records are written does not send them to the fallback again
+        let flaky = &stats.appenders[1];
+        assert!(flaky.healthy);
+        assert_eq!(flaky.last_error, Some("timeout".to_string()));
+        assert_eq!(written.args().len(), 10);
+        assert!(fallback.args().is_empty());
+        fastlog::shutdown(Some(Duration::from_secs(10))).unwrap();
+        let stats = fastlog::stats();
+        assert!(!stats.running);
+        assert!(stats.appenders.is_empty());
+    }
+}
@@ -0,0 +1,89 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::record;
+    use fastlog::appender::{Command, FastLogRecord, LogAppender};
+    use fastlog::filter::Filter;
+    use fastlog::plugin::subscriber::SubscriberAppender;
+    use log::{Level, LevelFilter};
+
+    fn lines(receiver: &fastlog::Receiver<FastLogRecord>) -> Vec<String> {
+        receiver.try_iter().map(|x| x.formated).collect()
+    }
+
+    struct TargetFilter(&'static str);
+
+    impl Filter for TargetFilter {
+        fn do_log(&self, record: &log::Record) -> bool {
+            record.target() == self.0
+        }
+    }
+
+    #[test]
+    fn test_fan_out() {
+        let mut appender = SubscriberAppender::new();
+        let subscribers = appender.subscribers();
+        appender.do_logs(&[record(Level::Info, "app", "before")]);
+        let all = subscribers.subscribe(10, LevelFilter::Trace);
+        let warn = subscribers.subscribe(10, LevelFilter::Warn);
+        let audit = subscribers.subscribe_filter(10, LevelFilter::Info, TargetFilter("audit"));
+        appender.do_logs(&[
+            record(Level::Debug, "app", "1"),
+            record(Level::Error, "app", "2"),
+            FastLogRecord::new_command(Command::CommandExit),
+            record(Level::Info, "audit", "3"),
+            record(Level::Debug, "audit", "4"),
+        ]);
+        assert_eq!(lines(&all), vec!["1", "2", "3", "4"]);
+        assert_eq!(lines(&warn), vec!["2"]);
+        assert_eq!(lines(&audit), vec!["3"]);
+        assert_eq!(subscribers.len(), 3);
+    }
+
+    #[test]
+    fn test_slow_subscriber() {
+        let mut appender = SubscriberAppender::new();
+        let subscribers = appender.subscribers();
+        let slow = subscribers.subscribe(2, LevelFilter::Trace);
+        let fast = subscribers.subscribe(10, LevelFilter::Trace);
+        appender.do_logs(&[
+            record(Level::Info, "app", "1"),
+            record(Level::Info, "app", "2"),
+            record(Level::Info, "app", "3"),
+            record(Level::Info, "app", "4"),
+        ]);
+        assert_eq!(lines(&slow), vec!["1", "2"]);
+        assert_eq!(lines(&fast), vec!["1", "2", "3", "4"]);
+        assert_eq!(subscribers.dropped(), 2);
+        appender.do_logs(&[record(Level::Info, "app", "5")]);
+        assert_eq!(lines(&slow), vec!["5"]);
+    }
+
+    #[test]
+    fn test_unsubscribe() {
+        let mut appender = SubscriberAppender::new();
+        let subscribers = appender.subscribers();
+        let kept = subscribers.subscribe(10, LevelFilter::Trace);
+        drop(subscribers.subscribe(10, LevelFilter::Trace));
+        assert_eq!(subscribers.len(), 2);
+        appender.do_logs(&[record(Level::Info, "app", "1")]);
+        assert_eq!(subscribers.len(), 1);
+        assert_eq!(subscribers.dropped(), 0);
+        assert_eq!(lines(&kept), vec!["1"]);
+    }
+
+    #[test]
+    fn test_logger() {
+        let appender = SubscriberAppender::new();
+        let subscribers = appender.subscribers();
+        fastlog::init(fastlog::Config::new().append(fastlog::AppenderConfig::new(appender)))
+            .unwrap();
+        let tail = subscribers.subscribe(100, LevelFilter::Info);
+        log::info!("live");
+        fastlog::flush().unwrap().wait();
+        let records: Vec<FastLogRecord> = tail.try_iter().collect();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].args, "live");
+    }
+}
@@ -0,0 +1,178 @@
+mod common;
+
+#[cfg(test)]
+mod test {
+    use crate::common::stamped;
+    use fastlog::appender::{Command, FastLogRecord, FieldValue, LogAppender};
+    use fastlog::plugin::syslog::{Facility, SyslogAppender, SyslogFormat};
+    use log::Level;
+    use std::io::Read;
+    use std::net::{TcpListener, UdpSocket};
+    use std::time::Duration;
+
+    #[test]
+    fn test_udp_rfc5424() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        server
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut appender = SyslogAppender::udp(&server.local_addr().unwrap().to_string())
+            .unwrap()
+            .facility(Facility::Local0)
+            .hostname("host")
+            .app_name("app")
+            .procid("42");
+        let mut with_fields = stamped(Level::Warn, "", "login");
+        with_fields.fields = vec![
+            ("user_id".to_string(), FieldValue::I64(42)),
+            (
+                "note".to_string(),
+                FieldValue::Str("a \"b\" [c] \\".to_string()),
+            ),
+        ];
+        appender.do_logs(&[stamped(Level::Info, "", "hello"), with_fields]);
+        let mut buf = [0u8; 1024];
+        let n = server.recv(&mut buf).unwrap();
+        assert_eq!(
+            std::str::from_utf8(&buf[..n]).unwrap(),
+            "<134>1 2023-11-14T22:13:20.123456Z host app 42 - - hello"
+        );
+        let n = server.recv(&mut buf).unwrap();
+        assert_eq!(
+            std::str::from_utf8(&buf[..n]).unwrap(),
+            "<132>1 2023-11-14T22:13:20.123456Z host app 42 - [fields@32473 user_id=\"42\" note=\"a \\\"b\\\" [c\\] \\\\\"] login"
+        );
+        assert!(appender.take_error().is_none());
+    }
+
+    #[test]
+    fn test_udp_oversized() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        server
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut appender = SyslogAppender::udp(&server.local_addr().unwrap().to_string())
+            .unwrap()
+            .backoff(Duration::from_secs(3600), Duration::from_secs(3600));
+        let large = "x".repeat(100 * 1024);
+        appender.do_logs(&[
+            stamped(Level::Info, "", &large),
+            stamped(Level::Info, "", "small"),
+        ]);
+        //only the oversized record is dropped,the socket is kept
+        let mut buf = [0u8; 1024];
+        let n = server.recv(&mut buf).unwrap();
+        assert!(std::str::from_utf8(&buf[..n]).unwrap().ends_with(" small"));
+        let error = appender.take_error().unwrap().to_string();
+        assert!(error.contains("bytes is dropped"), "{}", error);
+        appender.do_logs(&[stamped(Level::Info, "", "next")]);
+        let n = server.recv(&mut buf).unwrap();
+        assert!(std::str::from_utf8(&buf[..n]).unwrap().ends_with(" next"));
+        assert!(appender.take_error().is_none());
+    }
+
+    #[test]
+    fn test_tcp_octet_counting() {
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut appender = SyslogAppender::tcp(&server.local_addr().unwrap().to_string())
+            .unwrap()
+            .format(SyslogFormat::Rfc3164)
+            .facility(Facility::Daemon)
+            .hostname("host")
+            .app_name("app")
+            .procid("42");
+        let (mut stream, _) = server.accept().unwrap();
+        let mut with_fields = stamped(Level::Error, "", "boom");
+        with_fields.fields = vec![("tenant".to_string(), FieldValue::Str("a b".to_string()))];
+        appender.do_logs(&[stamped(Level::Debug, "", "hello"), with_fields]);
+        drop(appender);
+        let mut data = String::new();
+        stream.read_to_string(&mut data).unwrap();
+        let mut frames = vec![];
+        let mut rest = data.as_str();
+        while let Some((len, tail)) = rest.split_once(' ') {
+            let len: usize = len.parse().unwrap();
+            frames.push(&tail[..len]);
+            rest = &tail[len..];
+        }
+        assert_eq!(frames.len(), 2);
+        //RFC 3164 uses the local time
+        assert!(frames[0].starts_with("<31>Nov "));
+        assert!(frames[0].ends_with(" host app[42]: hello"));
+        assert!(frames[1].starts_with("<27>Nov "));
+        assert!(frames[1].ends_with(" host app[42]: boom tenant=\"a b\""));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unix() {
+        use std::os::unix::net::UnixDatagram;
+        let _ = std::fs::create_dir_all("target/test");
+        let path = format!("target/test/syslog_{}.sock", std::process::id());
+        let _ = std::fs::remove_file(&path);
+        let server = UnixDatagram::bind(&path).unwrap();
+        let mut appender = SyslogAppender::unix(&path)
+            .unwrap()
+            .hostname("")
+            .app_name("my app");
+        appender.do_logs(&[
+            FastLogRecord::new_command(Command::CommandExit),
+            stamped(Level::Trace, "", ""),
+        ]);
+        let mut buf = [0u8; 1024];
+        let n = server.recv(&mut buf).unwrap();
+        let msg = std::str::from_utf8(&buf[..n]).unwrap();
+        assert!(msg.starts_with("<15>1 2023-11-14T22:13:20.123456Z - myapp "));
+        assert!(msg.ends_with(" - -"));
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unix_reconnect() {
+        use std::os::unix::net::UnixDatagram;
+        let _ = std::fs::create_dir_all("target/test");
+        let path = format!("target/test/syslog_re_{}.sock", std::process::id());
+        let _ = std::fs::remove_file(&path);
+        let server = UnixDatagram::bind(&path).unwrap();
+        let mut appender = SyslogAppender::unix(&path).unwrap().app_name("app");
+        appender.do_logs(&[stamped(Level::Info, "", "a")]);
+        let mut buf = [0u8; 1024];
+        let n = server.recv(&mut buf).unwrap();
+        assert!(std::str::from_utf8(&buf[..n]).unwrap().ends_with(" a"));
+        //the socket is recreated,like a restarted syslog daemon
+        drop(server);
+        std::fs::remove_file(&path).unwrap();
+        let server = UnixDatagram::bind(&path).unwrap();
+        server
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        appender.do_logs(&[stamped(Level::Info, "", "b")]);
+        let n = server.recv(&mut buf).unwrap();
+        assert!(std::str::from_utf8(&buf[..n]).unwrap().ends_with(" b"));
+        assert!(appender.take_error().is_none());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_tcp_backoff() {
+        let server = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut appender = SyslogAppender::tcp(&server.local_addr().unwrap().to_string())
+            .unwrap()
+            .timeout(Duration::from_secs(1))
+            .backoff(Duration::from_secs(3600), Duration::from_secs(3600));
+        let (stream, _) = server.accept().unwrap();
+        drop(stream);
+        drop(server);
+        //the closed stream fails a write,then the connection is refused
+        let start = std::time::Instant::now();
+        while appender.take_error().is_none() {
+            assert!(start.elapsed() < Duration::from_secs(10));
+            appender.do_logs(&[stamped(Level::Info, "", "a")]);
+        }
+        //no connection is tried before the backoff is passed
+        appender.do_logs(&[stamped(Level::Info, "", "b"), stamped(Level::Info, "", "c")]);
+        let error = appender.take_error().unwrap().to_string();
+        assert!(error.contains("2 records are dropped"), "{}", error);
+    }
+}